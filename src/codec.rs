@@ -0,0 +1,240 @@
+use crate::error::ProofError;
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use std::convert::{TryFrom, TryInto};
+
+/// Checked decompression of a proof point, the one place a verifier rejects
+/// an invalid encoding.
+pub(crate) fn decompress(point: &CompressedRistretto) -> Result<RistrettoPoint, ProofError> {
+    point.decompress().ok_or(ProofError::InvalidEncoding)
+}
+
+pub(crate) fn decompress_all(
+    points: &[CompressedRistretto],
+) -> Result<Vec<RistrettoPoint>, ProofError> {
+    points.iter().map(decompress).collect()
+}
+
+/// Canonical proof encoding: points as 32-byte compressed Ristretto, scalars as
+/// their 32-byte canonical little-endian form, integers little-endian, vectors
+/// prefixed with their length as a little-endian u32.
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    pub fn point(&mut self, point: &RistrettoPoint) -> &mut Self {
+        self.bytes.extend_from_slice(point.compress().as_bytes());
+        self
+    }
+
+    pub fn compressed(&mut self, point: &CompressedRistretto) -> &mut Self {
+        self.bytes.extend_from_slice(point.as_bytes());
+        self
+    }
+
+    pub fn scalar(&mut self, scalar: &Scalar) -> &mut Self {
+        self.bytes.extend_from_slice(scalar.as_bytes());
+        self
+    }
+
+    pub fn byte(&mut self, byte: u8) -> &mut Self {
+        self.bytes.push(byte);
+        self
+    }
+
+    pub fn i64(&mut self, value: i64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn len(&mut self, len: usize) -> &mut Self {
+        let len = u32::try_from(len).expect("vector too long to encode");
+        self.bytes.extend_from_slice(&len.to_le_bytes());
+        self
+    }
+
+    pub fn points(&mut self, points: &[RistrettoPoint]) -> &mut Self {
+        self.len(points.len());
+        points.iter().for_each(|point| {
+            self.point(point);
+        });
+        self
+    }
+
+    pub fn compressed_points(&mut self, points: &[CompressedRistretto]) -> &mut Self {
+        self.len(points.len());
+        points.iter().for_each(|point| {
+            self.compressed(point);
+        });
+        self
+    }
+
+    pub fn scalars(&mut self, scalars: &[Scalar]) -> &mut Self {
+        self.len(scalars.len());
+        scalars.iter().for_each(|scalar| {
+            self.scalar(scalar);
+        });
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Strict counterpart of `Writer`: rejects invalid points, non-canonical
+/// scalars, lengths the input cannot hold and trailing bytes.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ProofError> {
+        if self.bytes.len() < n {
+            return Err(ProofError::InvalidEncoding);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn point(&mut self) -> Result<RistrettoPoint, ProofError> {
+        CompressedRistretto::from_slice(self.take(32)?)
+            .decompress()
+            .ok_or(ProofError::InvalidEncoding)
+    }
+
+    /// a point kept compressed, still checked to decompress
+    pub fn compressed(&mut self) -> Result<CompressedRistretto, ProofError> {
+        let point = CompressedRistretto::from_slice(self.take(32)?);
+        decompress(&point)?;
+        Ok(point)
+    }
+
+    pub fn scalar(&mut self) -> Result<Scalar, ProofError> {
+        let bytes: [u8; 32] = self.take(32)?.try_into().unwrap();
+        Scalar::from_canonical_bytes(bytes).ok_or(ProofError::InvalidEncoding)
+    }
+
+    pub fn byte(&mut self) -> Result<u8, ProofError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn i64(&mut self) -> Result<i64, ProofError> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// a vector length, checked against what is left so a forged prefix cannot
+    /// make us allocate more than the input could fill
+    fn len(&mut self) -> Result<usize, ProofError> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        if len > self.bytes.len() / 32 {
+            return Err(ProofError::InvalidEncoding);
+        }
+        Ok(len)
+    }
+
+    pub fn points(&mut self) -> Result<Vec<RistrettoPoint>, ProofError> {
+        let len = self.len()?;
+        (0..len).map(|_| self.point()).collect()
+    }
+
+    pub fn compressed_points(&mut self) -> Result<Vec<CompressedRistretto>, ProofError> {
+        let len = self.len()?;
+        (0..len).map(|_| self.compressed()).collect()
+    }
+
+    pub fn scalars(&mut self) -> Result<Vec<Scalar>, ProofError> {
+        let len = self.len()?;
+        (0..len).map(|_| self.scalar()).collect()
+    }
+
+    /// the bytes not read yet, e.g. a proof following its public params
+    pub fn rest(self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn finish(self) -> Result<(), ProofError> {
+        if !self.bytes.is_empty() {
+            return Err(ProofError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod codec_tests {
+    use super::*;
+    use wedpr_l_crypto_zkp_utils::{get_random_scalar, BASEPOINT_G1};
+
+    #[test]
+    fn round_trip_test() {
+        let point = get_random_scalar() * *BASEPOINT_G1;
+        let scalars = vec![get_random_scalar(), get_random_scalar()];
+        let mut writer = Writer::new();
+        writer
+            .point(&point)
+            .scalars(&scalars)
+            .compressed_points(&[])
+            .points(&[point])
+            .byte(64)
+            .i64(-5);
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes.len(), 32 + 4 + 64 + 4 + 4 + 32 + 1 + 8);
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.point(), Ok(point));
+        assert_eq!(reader.scalars(), Ok(scalars));
+        assert_eq!(reader.compressed_points(), Ok(vec![]));
+        assert_eq!(reader.points(), Ok(vec![point]));
+        assert_eq!(reader.byte(), Ok(64));
+        assert_eq!(reader.i64(), Ok(-5));
+        assert_eq!(reader.finish(), Ok(()));
+    }
+
+    #[test]
+    fn strict_decoding_test() {
+        // l + 1 is not a canonical scalar
+        let mut non_canonical = (-Scalar::one()).to_bytes();
+        non_canonical[0] += 2;
+        assert_eq!(
+            Reader::new(&non_canonical).scalar(),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        // not the encoding of any Ristretto point
+        assert_eq!(
+            Reader::new(&[0xff; 32]).point(),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        // a length the input cannot hold
+        let mut writer = Writer::new();
+        writer.len(1 << 20).scalar(&Scalar::one());
+        let bytes = writer.into_bytes();
+        assert_eq!(
+            Reader::new(&bytes).scalars(),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        // truncated and trailing input
+        assert_eq!(
+            Reader::new(&[0u8; 31]).scalar(),
+            Err(ProofError::InvalidEncoding)
+        );
+        let mut reader = Reader::new(&[0u8; 33]);
+        assert_eq!(reader.scalar(), Ok(Scalar::zero()));
+        assert_eq!(reader.finish(), Err(ProofError::InvalidEncoding));
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+/// Why a proof was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofError {
+    /// the proof does not have the shape the statement requires,
+    /// e.g. wrong vector lengths or bit width
+    MalformedProof,
+    /// the first verification equation does not hold
+    FirstEquationFailed,
+    /// the second verification equation does not hold
+    SecondEquationFailed,
+    /// the divisor of a division is not proven non-zero
+    ZeroDivisor,
+    /// the public commitments do not satisfy the relation, e.g. [a] + [b] != [c]
+    HomomorphicMismatch,
+    /// the bit commitments do not recompose to the committed value
+    BitDecompositionMismatch,
+    /// a point of the proof is the identity, which no honest prover sends
+    DegeneratePoint,
+    /// the bytes are not a canonical encoding of the proof
+    InvalidEncoding,
+    /// the proof was encoded by a format version this build does not read
+    UnsupportedVersion,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            ProofError::MalformedProof => "malformed proof",
+            ProofError::FirstEquationFailed => "first verification equation failed",
+            ProofError::SecondEquationFailed => "second verification equation failed",
+            ProofError::ZeroDivisor => "divisor is not proven non-zero",
+            ProofError::HomomorphicMismatch => "commitments do not satisfy the relation",
+            ProofError::BitDecompositionMismatch => {
+                "bit commitments do not recompose to the committed value"
+            }
+            ProofError::DegeneratePoint => "proof contains the identity point",
+            ProofError::InvalidEncoding => "invalid proof encoding",
+            ProofError::UnsupportedVersion => "unsupported proof format version",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for ProofError {}
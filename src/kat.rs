@@ -0,0 +1,329 @@
+use crate::{
+    try_verify_any, Addition, AdditionProofWithPublicParams,
+    AggregatedComparisonProofWithPublicParams, AnyProof, BitWidth, CheckedAddition,
+    CheckedAdditionWithPublicParams, CheckedSubtraction, CheckedSubtractionWithPublicParams,
+    CommittedValue, CompactComparisonProofWithPublicParams, Comparison,
+    ComparisonGeWithPublicParams, ComparisonProofWithPublicParams, Division,
+    DivisionWithPublicParams, DivisionWithRemainder, DivisionWithRemainderWithPublicParams,
+    Equality, EqualityWithPublicParams, Multiplication, MultiplicationWithPublicParams, Range,
+    RangeProofWithPublicParams, Substraction, SubstractionWithPublicParams,
+};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde_json::{json, Value};
+
+const VECTORS: &str = include_str!("../kat/vectors.json");
+
+fn hex_point(point: &RistrettoPoint) -> String {
+    hex(point.compress().as_bytes())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn width(bits: i64) -> BitWidth {
+    match bits {
+        8 => BitWidth::Bits8,
+        16 => BitWidth::Bits16,
+        32 => BitWidth::Bits32,
+        64 => BitWidth::Bits64,
+        _ => unreachable!("unsupported width {}", bits),
+    }
+}
+
+/// the relation's commitments and envelope for `inputs`, everything drawn from
+/// `seed`
+fn generate(relation: &str, seed: u8, inputs: &[i64]) -> (Vec<RistrettoPoint>, AnyProof) {
+    let mut rng = ChaCha20Rng::from_seed([seed; 32]);
+    match relation {
+        "add" => {
+            let a = Addition::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = Addition::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = Addition::commit_c_witness(inputs[2] as u64, a.secret.secret, b.secret.secret);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = Addition::prove_with_rng(&a, &b, &c, &mut rng);
+            let params = AdditionProofWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "sub" => {
+            let a = Substraction::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = Substraction::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c =
+                Substraction::commit_c_witness(inputs[2] as u64, a.secret.secret, b.secret.secret);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = Substraction::prove_with_rng(&a, &b, &c, &mut rng);
+            let params = SubstractionWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "mul" => {
+            let a = Multiplication::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = Multiplication::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = Multiplication::commit_with_rng(inputs[2] as u64, &mut rng);
+            let witness =
+                Multiplication::witness(a.secret.secret, c.secret.secret, inputs[1] as u64);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = Multiplication::prove_with_rng(&a, &b, &c, &witness, &mut rng);
+            let params = MultiplicationWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "div" => {
+            let a = Division::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = Division::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = Division::commit_with_rng(inputs[2] as u64, &mut rng);
+            let witness = Division::witness(a.secret.secret, c.secret.secret, inputs[1] as u64);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = Division::prove_with_rng(&a, &b, &c, &witness, &mut rng);
+            let params = DivisionWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "eq" => {
+            let a = Equality::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = Equality::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = Equality::commit_c_witness(a.secret.secret, b.secret.secret);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = Equality::prove_with_rng(&a, &b, &c, &mut rng);
+            let params = EqualityWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "cmp" => {
+            let x = Comparison::commit_with_rng(inputs[0] as i32, &mut rng);
+            let points = vec![x.comm.point];
+            let proof = Comparison::prove_with_rng(&x, &mut rng);
+            let params = ComparisonProofWithPublicParams {
+                proof,
+                x: points[0],
+            };
+            (points, params.into())
+        }
+        "divrem" => {
+            let a = DivisionWithRemainder::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = DivisionWithRemainder::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = DivisionWithRemainder::commit_with_rng(inputs[2] as u64, &mut rng);
+            let r = DivisionWithRemainder::commit_with_rng(inputs[3] as u64, &mut rng);
+            let points = vec![a.comm.point, b.comm.point, c.comm.point, r.comm.point];
+            let proof = DivisionWithRemainder::prove_with_rng(&a, &b, &c, &r, &mut rng);
+            let params = DivisionWithRemainderWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+                r_point: points[3],
+            };
+            (points, params.into())
+        }
+        "checked_sub" => {
+            let a = CheckedSubtraction::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = CheckedSubtraction::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = CheckedSubtraction::commit_c_witness(
+                inputs[2] as u64,
+                a.secret.secret,
+                b.secret.secret,
+            );
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = CheckedSubtraction::prove_with_rng(&a, &b, &c, &mut rng);
+            let params = CheckedSubtractionWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        "checked_add" => {
+            let a = CheckedAddition::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = CheckedAddition::commit_with_rng(inputs[1] as u64, &mut rng);
+            let c = CheckedAddition::commit_c_witness(
+                inputs[2] as u64,
+                a.secret.secret,
+                b.secret.secret,
+            );
+            let points = vec![a.comm.point, b.comm.point, c.comm.point];
+            let proof = CheckedAddition::prove_with_rng(&a, &b, &c, &mut rng);
+            let params = CheckedAdditionWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+                c_point: points[2],
+            };
+            (points, params.into())
+        }
+        // x, lo, hi
+        "range" => {
+            let x = Range::commit_with_rng(inputs[0], &mut rng);
+            let points = vec![x.comm.point];
+            let proof = Range::prove_with_rng(&x, inputs[1], inputs[2], &mut rng);
+            let params = RangeProofWithPublicParams {
+                proof,
+                x: points[0],
+                lo: inputs[1],
+                hi: inputs[2],
+            };
+            (points, params.into())
+        }
+        "ge" => {
+            let a = CommittedValue::commit_with_rng(inputs[0] as u64, &mut rng);
+            let b = CommittedValue::commit_with_rng(inputs[1] as u64, &mut rng);
+            let points = vec![a.comm.point, b.comm.point];
+            let proof = Comparison::prove_ge_with_rng(&a, &b, &mut rng);
+            let params = ComparisonGeWithPublicParams {
+                proof,
+                a_point: points[0],
+                b_point: points[1],
+            };
+            (points, params.into())
+        }
+        // x, then the width in bits
+        "compact" => {
+            let width = width(inputs[1]);
+            let x = Comparison::commit_with_width_and_rng(inputs[0], width, &mut rng);
+            let points = vec![x.comm.point];
+            let proof = Comparison::prove_compact_with_rng(&x, width, &mut rng);
+            let params = CompactComparisonProofWithPublicParams {
+                proof,
+                x: points[0],
+            };
+            (points, (params, width).into())
+        }
+        // x_1, ..., x_m, then the width in bits
+        "aggregated" => {
+            let (bits, values) = inputs.split_last().unwrap();
+            let width = width(*bits);
+            let x_vec: Vec<Comparison> = values
+                .iter()
+                .map(|value| Comparison::commit_with_width_and_rng(*value, width, &mut rng))
+                .collect();
+            let points: Vec<RistrettoPoint> = x_vec.iter().map(|x| x.comm.point).collect();
+            let proof = Comparison::prove_aggregated_with_rng(&x_vec, width, &mut rng);
+            let params = AggregatedComparisonProofWithPublicParams {
+                proof,
+                x_vec: points.clone(),
+            };
+            (points, (params, width).into())
+        }
+        _ => unreachable!("unknown relation {}", relation),
+    }
+}
+
+/// (relation, inputs, tampered): each relation once as proved, once with the
+/// lowest bit of the last proof scalar flipped
+fn cases() -> Vec<(&'static str, Vec<i64>, bool)> {
+    let inputs: Vec<(&str, Vec<i64>)> = vec![
+        ("add", vec![15, 18, 33]),
+        ("sub", vec![30, 18, 12]),
+        ("mul", vec![6, 7, 42]),
+        ("div", vec![42, 6, 7]),
+        ("eq", vec![30, 30]),
+        ("cmp", vec![209348]),
+        ("divrem", vec![30, 7, 4, 2]),
+        ("checked_sub", vec![30, 18, 12]),
+        ("checked_add", vec![15, 18, 33]),
+        ("range", vec![-3, -10, 10]),
+        ("ge", vec![30, 18]),
+        ("compact", vec![200, 8]),
+        ("aggregated", vec![30, 6, 16]),
+    ];
+    inputs
+        .into_iter()
+        .flat_map(|(relation, inputs)| {
+            vec![(relation, inputs.clone(), false), (relation, inputs, true)]
+        })
+        .collect()
+}
+
+fn vectors() -> Value {
+    let vectors: Vec<Value> = cases()
+        .into_iter()
+        .enumerate()
+        .map(|(i, (relation, inputs, tampered))| {
+            let seed = i as u8;
+            let (points, proof) = generate(relation, seed, &inputs);
+            let mut envelope = proof.to_bytes();
+            if tampered {
+                let last_scalar = envelope.len() - 32;
+                envelope[last_scalar] ^= 1;
+            }
+            json!({
+                "relation": relation,
+                "seed": hex(&[seed; 32]),
+                "inputs": inputs,
+                "commitments": points.iter().map(hex_point).collect::<Vec<_>>(),
+                "envelope": hex(&envelope),
+                "valid": !tampered,
+            })
+        })
+        .collect();
+    Value::Array(vectors)
+}
+
+/// Known-answer vectors in `kat/vectors.json`. Each vector names a relation,
+/// the ChaCha20 seed every commitment and proof was drawn from, the inputs, the
+/// commitments, the proof as an `AnyProof` envelope and the expected verdict.
+/// The test regenerates every vector from its seed and compares byte for byte,
+/// then verifies the stored envelope. Run it with `SVMZK_REGENERATE_KAT=1` to
+/// rewrite the file after an intentional format change.
+#[test]
+fn known_answer_test() {
+    let expected = vectors();
+    if std::env::var_os("SVMZK_REGENERATE_KAT").is_some() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/kat/vectors.json");
+        let text = serde_json::to_string_pretty(&expected).unwrap();
+        std::fs::write(path, text + "\n").unwrap();
+        return;
+    }
+
+    let stored: Value = serde_json::from_str(VECTORS).unwrap();
+    let stored = stored.as_array().unwrap();
+    assert_eq!(stored.len(), cases().len());
+
+    // every relation an envelope can name has vectors
+    let mut relation_ids: Vec<u8> = cases()
+        .into_iter()
+        .map(|(relation, inputs, _)| generate(relation, 0, &inputs).1.relation_id())
+        .collect();
+    relation_ids.dedup();
+    assert_eq!(relation_ids, (1..=13).collect::<Vec<u8>>());
+    for (vector, expected) in stored.iter().zip(expected.as_array().unwrap()) {
+        // the same seed gives the same commitments and proof, byte for byte
+        assert_eq!(vector, expected);
+
+        let envelope = vector["envelope"].as_str().unwrap();
+        let bytes: Vec<u8> = (0..envelope.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&envelope[i..i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(
+            try_verify_any(&bytes).is_ok(),
+            vector["valid"].as_bool().unwrap(),
+            "{} vector with seed {}",
+            vector["relation"],
+            vector["seed"]
+        );
+    }
+}
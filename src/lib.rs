@@ -1,18 +1,47 @@
 #![allow(unused_assignments)]
 #![allow(non_snake_case)]
 
+mod codec;
+mod error;
 mod protocol;
+mod rng;
+#[cfg(feature = "serde")]
+mod serde_hex;
+mod transcript;
 
+pub use error::ProofError;
 pub use protocol::{
-    add::{Addition, AdditionProofWithPublicParams},
-    com::{Comparison, ComparisonProofWithPublicParams},
-    div::{Division, DivisionWithPublicParams},
-    eq::{Equality, EqualityWithPublicParams},
-    mul::{Multiplication, MultiplicationWithPublicParams},
-    sub::{Substraction, SubstractionWithPublicParams},
+    add::{Addition, AdditionProof, AdditionProofWithPublicParams},
+    any::{
+        try_verify_any, try_verify_any_with_context, verify_any, verify_any_with_context, AnyProof,
+    },
+    bulletproof::{
+        AggregatedComparisonProofWithPublicParams, CompactComparisonProof,
+        CompactComparisonProofWithPublicParams, InnerProductProof,
+    },
+    checked_add::{CheckedAddition, CheckedAdditionProof, CheckedAdditionWithPublicParams},
+    checked_sub::{
+        CheckedSubtraction, CheckedSubtractionProof, CheckedSubtractionWithPublicParams,
+    },
+    com::{
+        BitWidth, Comparison, ComparisonGeWithPublicParams, ComparisonProof,
+        ComparisonProofWithPublicParams,
+    },
+    commitment::{Commitment, CommittedValue, Opening, SignedCommittedValue, SignedOpening},
+    div::{Division, DivisionProof, DivisionWithPublicParams},
+    divrem::{
+        DivisionWithRemainder, DivisionWithRemainderProof, DivisionWithRemainderWithPublicParams,
+    },
+    eq::{Equality, EqualityProof, EqualityWithPublicParams},
+    mul::{Multiplication, MultiplicationProof, MultiplicationWithPublicParams},
+    range::{Range, RangeProof, RangeProofWithPublicParams},
+    sub::{Substraction, SubstractionProof, SubstractionWithPublicParams},
 };
 mod marco;
 
+#[cfg(test)]
+mod kat;
+
 #[cfg(test)]
 mod com_tests {
     use super::*;
@@ -54,10 +83,10 @@ mod com_tests {
         let proof = Comparison::prove(&comparison);
 
         let res = Comparison::verify(ComparisonProofWithPublicParams {
-            proof: proof,
+            proof,
             x: comparison.comm.point,
         });
-        assert_eq!(res, false);
+        assert!(!res);
     }
 
     /// prove x + y
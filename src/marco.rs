@@ -7,10 +7,10 @@ macro_rules! test_comparison {
         let proof = Comparison::prove(&comparison);
 
         let res = Comparison::verify(ComparisonProofWithPublicParams {
-            proof: proof,
+            proof,
             x: comparison.comm.point,
         });
-        assert_eq!(res, true);
+        assert!(res);
     };
 }
 
@@ -23,10 +23,10 @@ macro_rules! test_comparison_negative {
         let proof = Comparison::prove(&comparison);
 
         let res = Comparison::verify(ComparisonProofWithPublicParams {
-            proof: proof,
+            proof,
             x: comparison.comm.point,
         });
-        assert_eq!(res, false);
+        assert!(!res);
     };
 }
 
@@ -44,7 +44,7 @@ macro_rules! test_addition {
         let proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
 
         let result = Addition::verify(AdditionProofWithPublicParams {
-            proof: proof,
+            proof,
             a_point: a_comm_secret.comm.point,
             b_point: b_comm_secret.comm.point,
             c_point: c_comm_secret.comm.point,
@@ -68,7 +68,7 @@ macro_rules! test_substration {
         let proof = Substraction::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
 
         let result = Substraction::verify(SubstractionWithPublicParams {
-            proof: proof,
+            proof,
             a_point: a_comm_secret.comm.point,
             b_point: b_comm_secret.comm.point,
             c_point: c_comm_secret.comm.point,
@@ -91,13 +91,13 @@ macro_rules! test_multiplication {
         let proof = Multiplication::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
 
         let result = Multiplication::verify(MultiplicationWithPublicParams {
-            proof: proof,
+            proof,
             a_point: a_comm_secret.comm.point,
             b_point: b_comm_secret.comm.point,
             c_point: c_comm_secret.comm.point,
         });
 
-        assert_eq!(true, result);
+        assert!(result);
     };
 }
 
@@ -114,7 +114,7 @@ macro_rules! test_division {
         let proof = Division::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
 
         let result = Division::verify(DivisionWithPublicParams {
-            proof: proof,
+            proof,
             a_point: a_comm_secret.comm.point,
             b_point: b_comm_secret.comm.point,
             c_point: c_comm_secret.comm.point,
@@ -135,7 +135,7 @@ macro_rules! test_equality {
         let proof = Equality::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
 
         let result = Equality::verify(EqualityWithPublicParams {
-            proof: proof,
+            proof,
             a_point: a_comm_secret.comm.point,
             b_point: b_comm_secret.comm.point,
             c_point: c_comm_secret.comm.point,
@@ -1,142 +1,312 @@
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
-use wedpr_l_crypto_zkp_utils::{
-    get_random_scalar, hash_to_scalar, point_to_bytes, BASEPOINT_G1, BASEPOINT_G2,
+use crate::codec::{decompress, Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::commitment::{
+    scalar_from_i128, Commitment, CommittedValue, Opening, SignedCommittedValue, SignedOpening,
 };
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, MultiscalarMul},
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
 
 /// a + b = c
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Addition {
-    pub comm: AdditionCommitment,
-    pub secret: AdditionSecret,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct AdditionCommitment {
-    pub point: RistrettoPoint,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct AdditionSecret {
-    pub value: u64,
-    pub secret: Scalar,
-}
+pub struct Addition;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdditionProof {
-    pub d: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub u: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub v: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AdditionProofWithPublicParams {
     pub proof: AdditionProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub c_point: RistrettoPoint,
 }
 
+impl AdditionProof {
+    /// canonical encoding, 96 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.compressed(&self.d).scalar(&self.u).scalar(&self.v);
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, ProofError> {
+        Ok(Self {
+            d: reader.compressed()?,
+            u: reader.scalar()?,
+            v: reader.scalar()?,
+        })
+    }
+}
+
 impl Addition {
-    pub fn commit(value: u64) -> Self {
-        let secret = get_random_scalar();
-        let commitment_point = RistrettoPoint::multiscalar_mul(
-            &[Scalar::from(value), secret],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
 
-        Self {
-            comm: AdditionCommitment {
-                point: commitment_point,
-            },
-            secret: AdditionSecret { value, secret },
-        }
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
     }
 
-    pub fn commit_c_witness(value: u64, a_sec: Scalar, b_sec: Scalar) -> Self {
+    pub fn commit_c_witness(value: u64, a_sec: Scalar, b_sec: Scalar) -> CommittedValue {
         let commitment_point = RistrettoPoint::multiscalar_mul(
             &[Scalar::from(value), a_sec + b_sec],
             &[*BASEPOINT_G1, *BASEPOINT_G2],
         );
 
-        Self {
-            comm: AdditionCommitment {
+        CommittedValue {
+            comm: Commitment {
                 point: commitment_point,
             },
-            secret: AdditionSecret {
+            secret: Opening {
                 value,
                 secret: a_sec + b_sec,
             },
         }
     }
 
-    pub fn prove(a: &Addition, b: &Addition, c: &Addition) -> AdditionProof {
-        let Addition {
-            comm: AdditionCommitment { point: a_point },
+    /// commit to a signed value, see `SignedCommittedValue`
+    pub fn commit_i64(value: i64) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value as i128)
+    }
+
+    pub fn commit_i128(value: i128) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value)
+    }
+
+    pub fn commit_i64_with_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value as i128, rng)
+    }
+
+    pub fn commit_i128_with_rng<R: RngCore + CryptoRng>(
+        value: i128,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_c_witness_i64(value: i64, a_sec: Scalar, b_sec: Scalar) -> SignedCommittedValue {
+        Self::commit_c_witness_i128(value as i128, a_sec, b_sec)
+    }
+
+    pub fn commit_c_witness_i128(
+        value: i128,
+        a_sec: Scalar,
+        b_sec: Scalar,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_blinding(value, a_sec + b_sec)
+    }
+
+    pub fn prove(a: &CommittedValue, b: &CommittedValue, c: &CommittedValue) -> AdditionProof {
+        Self::prove_with_context(a, b, c, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+    ) -> AdditionProof {
+        Self::prove_with_context_and_rng(a, b, c, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        rng: &mut R,
+    ) -> AdditionProof {
+        Self::prove_with_context_and_rng(a, b, c, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> AdditionProof {
+        Self::prove_signed_with_context_and_rng(
+            &SignedCommittedValue::from(a),
+            &SignedCommittedValue::from(b),
+            &SignedCommittedValue::from(c),
+            context,
+            rng,
+        )
+    }
+
+    /// prove for signed commitments, every relation holding in the scalar
+    /// field where a negative v is −|v|
+    pub fn prove_signed(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+    ) -> AdditionProof {
+        Self::prove_signed_with_context(a, b, c, &[])
+    }
+
+    pub fn prove_signed_with_context(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        context: &[u8],
+    ) -> AdditionProof {
+        Self::prove_signed_with_context_and_rng(a, b, c, context, &mut OsRng)
+    }
+
+    pub fn prove_signed_with_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        rng: &mut R,
+    ) -> AdditionProof {
+        Self::prove_signed_with_context_and_rng(a, b, c, &[], rng)
+    }
+
+    pub fn prove_signed_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> AdditionProof {
+        let SignedCommittedValue {
+            comm: Commitment { point: a_point },
             secret:
-                AdditionSecret {
+                SignedOpening {
                     value: _a_value,
                     secret: _a_secret,
                 },
         } = a;
-        let Addition {
-            comm: AdditionCommitment { point: b_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: b_point },
             secret:
-                AdditionSecret {
+                SignedOpening {
                     value: _b_value,
                     secret: _b_secret,
                 },
         } = b;
-        let Addition {
-            comm: AdditionCommitment { point: c_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: c_point },
             secret:
-                AdditionSecret {
+                SignedOpening {
                     value: c_value,
                     secret: c_secret,
                 },
         } = c;
 
-        let x = get_random_scalar();
-        let y = get_random_scalar();
+        let mut transcript = Transcript::new(Protocol::Addition, context);
+        transcript.append_point(b"a", a_point);
+        transcript.append_point(b"b", b_point);
+        transcript.append_point(b"c", c_point);
+
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_scalar(b"c_value", &scalar_from_i128(*c_value))
+            .rekey_with_witness_scalar(b"c_secret", c_secret)
+            .finalize(rng);
+        let x = random_scalar(&mut rng);
+        let y = random_scalar(&mut rng);
         let d_point = RistrettoPoint::multiscalar_mul(&[x, y], &[*BASEPOINT_G1, *BASEPOINT_G2]);
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(a_point));
-        hash_vec.append(&mut point_to_bytes(b_point));
-        hash_vec.append(&mut point_to_bytes(c_point));
-        hash_vec.append(&mut point_to_bytes(&d_point));
+        transcript.append_point(b"d", &d_point);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         //let u = x + (Scalar::from(_a_value.clone()) + Scalar::from(b_value.clone())) * e;
-        let u = x + (Scalar::from(*c_value)) * e;
+        let u = x + (scalar_from_i128(*c_value)) * e;
         let v = y + (c_secret) * e;
-        AdditionProof { d: d_point, u, v }
+        AdditionProof {
+            d: d_point.compress(),
+            u,
+            v,
+        }
     }
 
     pub fn verify(proof: AdditionProofWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: AdditionProofWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: AdditionProofWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: AdditionProofWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
         let AdditionProofWithPublicParams {
             proof: AdditionProof { d: d_point, u, v },
             a_point,
             b_point,
             c_point,
         } = proof;
+        let d_point = decompress(&d_point)?;
+
+        if d_point.is_identity() {
+            return Err(ProofError::DegeneratePoint);
+        }
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(&a_point));
-        hash_vec.append(&mut point_to_bytes(&b_point));
-        hash_vec.append(&mut point_to_bytes(&c_point));
-        hash_vec.append(&mut point_to_bytes(&d_point));
+        let mut transcript = Transcript::new(Protocol::Addition, context);
+        transcript.append_point(b"a", &a_point);
+        transcript.append_point(b"b", &b_point);
+        transcript.append_point(b"c", &c_point);
+        transcript.append_point(b"d", &d_point);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         let c_cal = a_point + b_point;
         let left = d_point + e * c_point;
         let right = RistrettoPoint::multiscalar_mul(&[u, v], &[*BASEPOINT_G1, *BASEPOINT_G2]);
-        //assert_eq!(c_cal, c_point);
-        c_cal == c_point && left == right
+        if c_cal != c_point {
+            return Err(ProofError::HomomorphicMismatch);
+        }
+        if left != right {
+            return Err(ProofError::FirstEquationFailed);
+        }
+        Ok(())
     }
 }
 
@@ -145,6 +315,7 @@ mod addition_tests {
     use super::*;
     use rand::Rng;
     use std::assert_eq;
+    use wedpr_l_crypto_zkp_utils::get_random_scalar;
 
     #[test]
     fn it_works() {
@@ -170,7 +341,7 @@ mod addition_tests {
             a_comm_secret.comm.point + b_comm_secret.comm.point,
             c_comm_secret.comm.point
         );
-        assert_eq!(true, result);
+        assert!(result);
     }
 
     #[test]
@@ -204,7 +375,7 @@ mod addition_tests {
                 a_comm_secret.comm.point + b_comm_secret.comm.point,
                 c_comm_secret.comm.point
             );
-            assert_eq!(true, result);
+            assert!(result);
         }
     }
 
@@ -246,4 +417,168 @@ mod addition_tests {
         let right = RistrettoPoint::multiscalar_mul(&[u, v], &[*BASEPOINT_G1, *BASEPOINT_G2]);
         assert_eq!(left, right);
     }
+
+    #[test]
+    fn verify_error_test() {
+        let a_comm_secret = Addition::commit(15);
+        let b_comm_secret = Addition::commit(18);
+        let c_comm_secret = Addition::commit_c_witness(
+            34,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+
+        let proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+        let result = Addition::try_verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::HomomorphicMismatch));
+
+        let c_comm_secret = Addition::commit_c_witness(
+            33,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+        let mut proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+        proof.u += Scalar::one();
+
+        let result = Addition::try_verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::FirstEquationFailed));
+    }
+
+    #[test]
+    fn identity_point_test() {
+        let a_comm_secret = Addition::commit(30);
+        let b_comm_secret = Addition::commit(18);
+        let c_comm_secret = Addition::commit_c_witness(
+            48,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+
+        let mut proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+        proof.d = RistrettoPoint::default().compress();
+
+        let result = Addition::try_verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::DegeneratePoint));
+    }
+
+    #[test]
+    fn context_test() {
+        let a_comm_secret = Addition::commit(30);
+        let b_comm_secret = Addition::commit(18);
+        let c_comm_secret = Addition::commit_c_witness(
+            48,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+
+        let proof =
+            Addition::prove_with_context(&a_comm_secret, &b_comm_secret, &c_comm_secret, b"tx 1");
+        let params = AdditionProofWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        };
+
+        assert!(Addition::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Addition::verify_with_context(params.clone(), b"tx 2"));
+        assert_eq!(
+            Addition::try_verify(params),
+            Err(ProofError::FirstEquationFailed)
+        );
+    }
+
+    /// a repeated RNG output no longer repeats the nonce across witnesses,
+    /// so two proofs cannot be combined to solve for the secret
+    #[test]
+    fn repeated_rng_test() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let prove = |a_value: u64| {
+            let a = Addition::commit(a_value);
+            let b = Addition::commit(1);
+            let c = Addition::commit_c_witness(a_value + 1, a.secret.secret, b.secret.secret);
+            let proof = Addition::prove_with_rng(&a, &b, &c, &mut StdRng::seed_from_u64(0));
+            assert!(Addition::verify(AdditionProofWithPublicParams {
+                proof: proof.clone(),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: c.comm.point,
+            }));
+            proof
+        };
+
+        assert_ne!(prove(1).d, prove(2).d);
+    }
+
+    #[test]
+    fn bytes_test() {
+        let a_comm_secret = Addition::commit(15);
+        let b_comm_secret = Addition::commit(18);
+        let c_comm_secret = Addition::commit_c_witness(
+            33,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+        let proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 96);
+        let decoded = AdditionProof::from_bytes(&bytes).unwrap();
+        assert!(Addition::verify(AdditionProofWithPublicParams {
+            proof: decoded,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        }));
+
+        // v with its top byte set is not reduced mod l
+        let mut non_canonical = bytes.clone();
+        non_canonical[95] = 0xff;
+        assert_eq!(
+            AdditionProof::from_bytes(&non_canonical),
+            Err(ProofError::InvalidEncoding)
+        );
+        assert_eq!(
+            AdditionProof::from_bytes(&bytes[..95]),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn invalid_point_test() {
+        let a_comm_secret = Addition::commit(15);
+        let b_comm_secret = Addition::commit(18);
+        let c_comm_secret = Addition::commit_c_witness(
+            33,
+            a_comm_secret.secret.secret,
+            b_comm_secret.secret.secret,
+        );
+        let mut proof = Addition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+        proof.d = CompressedRistretto([0xff; 32]);
+
+        let result = Addition::try_verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::InvalidEncoding));
+    }
 }
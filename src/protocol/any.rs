@@ -0,0 +1,618 @@
+use crate::codec::{Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::{
+    add::{Addition, AdditionProof, AdditionProofWithPublicParams},
+    bulletproof::{
+        AggregatedComparisonProofWithPublicParams, CompactComparisonProof,
+        CompactComparisonProofWithPublicParams,
+    },
+    checked_add::{CheckedAddition, CheckedAdditionProof, CheckedAdditionWithPublicParams},
+    checked_sub::{
+        CheckedSubtraction, CheckedSubtractionProof, CheckedSubtractionWithPublicParams,
+    },
+    com::{
+        BitWidth, Comparison, ComparisonGeWithPublicParams, ComparisonProof,
+        ComparisonProofWithPublicParams,
+    },
+    div::{Division, DivisionProof, DivisionWithPublicParams},
+    divrem::{
+        DivisionWithRemainder, DivisionWithRemainderProof, DivisionWithRemainderWithPublicParams,
+    },
+    eq::{Equality, EqualityProof, EqualityWithPublicParams},
+    mul::{Multiplication, MultiplicationProof, MultiplicationWithPublicParams},
+    range::{Range, RangeProof, RangeProofWithPublicParams},
+    sub::{Substraction, SubstractionProof, SubstractionWithPublicParams},
+};
+
+/// first byte of every envelope
+const MAGIC: u8 = 0x5a;
+/// bumped whenever the envelope or a proof encoding changes
+const VERSION: u8 = 1;
+
+/// Self-describing proof: magic byte, format version, relation ID, then the
+/// public commitments and the proof in their canonical encodings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnyProof {
+    Addition(AdditionProofWithPublicParams),
+    Substraction(SubstractionWithPublicParams),
+    Multiplication(MultiplicationWithPublicParams),
+    Division(DivisionWithPublicParams),
+    Equality(EqualityWithPublicParams),
+    Comparison(ComparisonProofWithPublicParams),
+    DivisionWithRemainder(Box<DivisionWithRemainderWithPublicParams>),
+    CheckedSubtraction(CheckedSubtractionWithPublicParams),
+    CheckedAddition(CheckedAdditionWithPublicParams),
+    Range(RangeProofWithPublicParams),
+    ComparisonGe(ComparisonGeWithPublicParams),
+    /// the width is part of the statement, so the envelope carries it
+    CompactComparison(CompactComparisonProofWithPublicParams, BitWidth),
+    AggregatedComparison(AggregatedComparisonProofWithPublicParams, BitWidth),
+}
+
+impl AnyProof {
+    /// the relation ID stored in the envelope
+    pub fn relation_id(&self) -> u8 {
+        match self {
+            AnyProof::Addition(_) => 1,
+            AnyProof::Substraction(_) => 2,
+            AnyProof::Multiplication(_) => 3,
+            AnyProof::Division(_) => 4,
+            AnyProof::Equality(_) => 5,
+            AnyProof::Comparison(_) => 6,
+            AnyProof::DivisionWithRemainder(_) => 7,
+            AnyProof::CheckedSubtraction(_) => 8,
+            AnyProof::CheckedAddition(_) => 9,
+            AnyProof::Range(_) => 10,
+            AnyProof::ComparisonGe(_) => 11,
+            AnyProof::CompactComparison(..) => 12,
+            AnyProof::AggregatedComparison(..) => 13,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![MAGIC, VERSION, self.relation_id()];
+        let mut writer = Writer::new();
+        let proof = match self {
+            AnyProof::Addition(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Substraction(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Multiplication(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Division(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Equality(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Comparison(params) => {
+                writer.point(&params.x);
+                params.proof.to_bytes()
+            }
+            AnyProof::DivisionWithRemainder(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point)
+                    .point(&params.r_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::CheckedSubtraction(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::CheckedAddition(params) => {
+                writer
+                    .point(&params.a_point)
+                    .point(&params.b_point)
+                    .point(&params.c_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::Range(params) => {
+                writer.point(&params.x).i64(params.lo).i64(params.hi);
+                params.proof.to_bytes()
+            }
+            AnyProof::ComparisonGe(params) => {
+                writer.point(&params.a_point).point(&params.b_point);
+                params.proof.to_bytes()
+            }
+            AnyProof::CompactComparison(params, width) => {
+                writer.byte(width.bits() as u8).point(&params.x);
+                params.proof.to_bytes()
+            }
+            AnyProof::AggregatedComparison(params, width) => {
+                writer.byte(width.bits() as u8).points(&params.x_vec);
+                params.proof.to_bytes()
+            }
+        };
+        bytes.extend_from_slice(&writer.into_bytes());
+        bytes.extend_from_slice(&proof);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let (header, body) = match bytes {
+            [magic, version, relation_id, body @ ..] => ([*magic, *version, *relation_id], body),
+            _ => return Err(ProofError::InvalidEncoding),
+        };
+        if header[0] != MAGIC {
+            return Err(ProofError::InvalidEncoding);
+        }
+        if header[1] != VERSION {
+            return Err(ProofError::UnsupportedVersion);
+        }
+
+        let mut reader = Reader::new(body);
+        let proof = match header[2] {
+            1 => AnyProof::Addition(AdditionProofWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: AdditionProof::from_bytes(reader.rest())?,
+            }),
+            2 => AnyProof::Substraction(SubstractionWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: SubstractionProof::from_bytes(reader.rest())?,
+            }),
+            3 => AnyProof::Multiplication(MultiplicationWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: MultiplicationProof::from_bytes(reader.rest())?,
+            }),
+            4 => AnyProof::Division(DivisionWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: DivisionProof::from_bytes(reader.rest())?,
+            }),
+            5 => AnyProof::Equality(EqualityWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: EqualityProof::from_bytes(reader.rest())?,
+            }),
+            6 => AnyProof::Comparison(ComparisonProofWithPublicParams {
+                x: reader.point()?,
+                proof: ComparisonProof::from_bytes(reader.rest())?,
+            }),
+            7 => AnyProof::DivisionWithRemainder(Box::new(DivisionWithRemainderWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                r_point: reader.point()?,
+                proof: DivisionWithRemainderProof::from_bytes(reader.rest())?,
+            })),
+            8 => AnyProof::CheckedSubtraction(CheckedSubtractionWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: CheckedSubtractionProof::from_bytes(reader.rest())?,
+            }),
+            9 => AnyProof::CheckedAddition(CheckedAdditionWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                c_point: reader.point()?,
+                proof: CheckedAdditionProof::from_bytes(reader.rest())?,
+            }),
+            10 => AnyProof::Range(RangeProofWithPublicParams {
+                x: reader.point()?,
+                lo: reader.i64()?,
+                hi: reader.i64()?,
+                proof: RangeProof::from_bytes(reader.rest())?,
+            }),
+            11 => AnyProof::ComparisonGe(ComparisonGeWithPublicParams {
+                a_point: reader.point()?,
+                b_point: reader.point()?,
+                proof: ComparisonProof::from_bytes(reader.rest())?,
+            }),
+            12 => {
+                let width = read_width(&mut reader)?;
+                AnyProof::CompactComparison(
+                    CompactComparisonProofWithPublicParams {
+                        x: reader.point()?,
+                        proof: CompactComparisonProof::from_bytes(reader.rest())?,
+                    },
+                    width,
+                )
+            }
+            13 => {
+                let width = read_width(&mut reader)?;
+                AnyProof::AggregatedComparison(
+                    AggregatedComparisonProofWithPublicParams {
+                        x_vec: reader.points()?,
+                        proof: CompactComparisonProof::from_bytes(reader.rest())?,
+                    },
+                    width,
+                )
+            }
+            _ => return Err(ProofError::InvalidEncoding),
+        };
+        Ok(proof)
+    }
+
+    pub fn verify(self) -> bool {
+        self.try_verify().is_ok()
+    }
+
+    pub fn try_verify(self) -> Result<(), ProofError> {
+        self.try_verify_with_context(&[])
+    }
+
+    pub fn verify_with_context(self, context: &[u8]) -> bool {
+        self.try_verify_with_context(context).is_ok()
+    }
+
+    pub fn try_verify_with_context(self, context: &[u8]) -> Result<(), ProofError> {
+        match self {
+            AnyProof::Addition(params) => Addition::try_verify_with_context(params, context),
+            AnyProof::Substraction(params) => {
+                Substraction::try_verify_with_context(params, context)
+            }
+            AnyProof::Multiplication(params) => {
+                Multiplication::try_verify_with_context(params, context)
+            }
+            AnyProof::Division(params) => Division::try_verify_with_context(params, context),
+            AnyProof::Equality(params) => Equality::try_verify_with_context(params, context),
+            AnyProof::Comparison(params) => Comparison::try_verify_with_context(params, context),
+            AnyProof::DivisionWithRemainder(params) => {
+                DivisionWithRemainder::try_verify_with_context(*params, context)
+            }
+            AnyProof::CheckedSubtraction(params) => {
+                CheckedSubtraction::try_verify_with_context(params, context)
+            }
+            AnyProof::CheckedAddition(params) => {
+                CheckedAddition::try_verify_with_context(params, context)
+            }
+            AnyProof::Range(params) => Range::try_verify_with_context(params, context),
+            AnyProof::ComparisonGe(params) => {
+                Comparison::try_verify_ge_with_context(params, context)
+            }
+            AnyProof::CompactComparison(params, width) => {
+                Comparison::try_verify_compact_with_context(params, width, context)
+            }
+            AnyProof::AggregatedComparison(params, width) => {
+                Comparison::try_verify_aggregated_with_context(params, width, context)
+            }
+        }
+    }
+}
+
+/// the width byte of a compact or aggregated envelope, its number of bits
+fn read_width(reader: &mut Reader) -> Result<BitWidth, ProofError> {
+    match reader.byte()? {
+        8 => Ok(BitWidth::Bits8),
+        16 => Ok(BitWidth::Bits16),
+        32 => Ok(BitWidth::Bits32),
+        64 => Ok(BitWidth::Bits64),
+        _ => Err(ProofError::InvalidEncoding),
+    }
+}
+
+impl From<AdditionProofWithPublicParams> for AnyProof {
+    fn from(params: AdditionProofWithPublicParams) -> Self {
+        AnyProof::Addition(params)
+    }
+}
+
+impl From<SubstractionWithPublicParams> for AnyProof {
+    fn from(params: SubstractionWithPublicParams) -> Self {
+        AnyProof::Substraction(params)
+    }
+}
+
+impl From<MultiplicationWithPublicParams> for AnyProof {
+    fn from(params: MultiplicationWithPublicParams) -> Self {
+        AnyProof::Multiplication(params)
+    }
+}
+
+impl From<DivisionWithPublicParams> for AnyProof {
+    fn from(params: DivisionWithPublicParams) -> Self {
+        AnyProof::Division(params)
+    }
+}
+
+impl From<EqualityWithPublicParams> for AnyProof {
+    fn from(params: EqualityWithPublicParams) -> Self {
+        AnyProof::Equality(params)
+    }
+}
+
+impl From<ComparisonProofWithPublicParams> for AnyProof {
+    fn from(params: ComparisonProofWithPublicParams) -> Self {
+        AnyProof::Comparison(params)
+    }
+}
+
+impl From<DivisionWithRemainderWithPublicParams> for AnyProof {
+    fn from(params: DivisionWithRemainderWithPublicParams) -> Self {
+        AnyProof::DivisionWithRemainder(Box::new(params))
+    }
+}
+
+impl From<CheckedSubtractionWithPublicParams> for AnyProof {
+    fn from(params: CheckedSubtractionWithPublicParams) -> Self {
+        AnyProof::CheckedSubtraction(params)
+    }
+}
+
+impl From<CheckedAdditionWithPublicParams> for AnyProof {
+    fn from(params: CheckedAdditionWithPublicParams) -> Self {
+        AnyProof::CheckedAddition(params)
+    }
+}
+
+impl From<RangeProofWithPublicParams> for AnyProof {
+    fn from(params: RangeProofWithPublicParams) -> Self {
+        AnyProof::Range(params)
+    }
+}
+
+impl From<ComparisonGeWithPublicParams> for AnyProof {
+    fn from(params: ComparisonGeWithPublicParams) -> Self {
+        AnyProof::ComparisonGe(params)
+    }
+}
+
+impl From<(CompactComparisonProofWithPublicParams, BitWidth)> for AnyProof {
+    fn from((params, width): (CompactComparisonProofWithPublicParams, BitWidth)) -> Self {
+        AnyProof::CompactComparison(params, width)
+    }
+}
+
+impl From<(AggregatedComparisonProofWithPublicParams, BitWidth)> for AnyProof {
+    fn from((params, width): (AggregatedComparisonProofWithPublicParams, BitWidth)) -> Self {
+        AnyProof::AggregatedComparison(params, width)
+    }
+}
+
+/// decode an envelope and verify it with the relation it names, under the
+/// empty context; a proof made with a context only verifies through
+/// `verify_any_with_context`
+pub fn verify_any(bytes: &[u8]) -> bool {
+    try_verify_any(bytes).is_ok()
+}
+
+pub fn try_verify_any(bytes: &[u8]) -> Result<(), ProofError> {
+    try_verify_any_with_context(bytes, &[])
+}
+
+/// decode an envelope and verify it under the context it was proven with
+pub fn verify_any_with_context(bytes: &[u8], context: &[u8]) -> bool {
+    try_verify_any_with_context(bytes, context).is_ok()
+}
+
+pub fn try_verify_any_with_context(bytes: &[u8], context: &[u8]) -> Result<(), ProofError> {
+    AnyProof::from_bytes(bytes)?.try_verify_with_context(context)
+}
+
+#[cfg(test)]
+mod any_tests {
+    use super::*;
+    use crate::protocol::commitment::CommittedValue;
+
+    fn envelopes() -> Vec<AnyProof> {
+        let a = CommittedValue::commit(30);
+        let b = CommittedValue::commit(6);
+        let sum = Addition::commit_c_witness(36, a.secret.secret, b.secret.secret);
+        let difference = Substraction::commit_c_witness(24, a.secret.secret, b.secret.secret);
+        let product = CommittedValue::commit(180);
+        let quotient = CommittedValue::commit(5);
+        let a_again = CommittedValue::commit(30);
+        let zero = Equality::commit_c_witness(a.secret.secret, a_again.secret.secret);
+        let x = Comparison::commit(30);
+        let divisor = CommittedValue::commit(7);
+        let remainder = CommittedValue::commit(2);
+        let whole_quotient = CommittedValue::commit(4);
+        let signed = Range::commit(-3);
+        let small = CommittedValue::commit(200);
+
+        let mul_witness = Multiplication::witness(a.secret.secret, product.secret.secret, 6);
+        let div_witness = Division::witness(a.secret.secret, quotient.secret.secret, 6);
+
+        vec![
+            AdditionProofWithPublicParams {
+                proof: Addition::prove(&a, &b, &sum),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: sum.comm.point,
+            }
+            .into(),
+            SubstractionWithPublicParams {
+                proof: Substraction::prove(&a, &b, &difference),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: difference.comm.point,
+            }
+            .into(),
+            MultiplicationWithPublicParams {
+                proof: Multiplication::prove(&a, &b, &product, &mul_witness),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: product.comm.point,
+            }
+            .into(),
+            DivisionWithPublicParams {
+                proof: Division::prove(&a, &b, &quotient, &div_witness),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: quotient.comm.point,
+            }
+            .into(),
+            EqualityWithPublicParams {
+                proof: Equality::prove(&a, &a_again, &zero),
+                a_point: a.comm.point,
+                b_point: a_again.comm.point,
+                c_point: zero.comm.point,
+            }
+            .into(),
+            ComparisonProofWithPublicParams {
+                proof: Comparison::prove(&x),
+                x: x.comm.point,
+            }
+            .into(),
+            DivisionWithRemainderWithPublicParams {
+                proof: DivisionWithRemainder::prove(&a, &divisor, &whole_quotient, &remainder),
+                a_point: a.comm.point,
+                b_point: divisor.comm.point,
+                c_point: whole_quotient.comm.point,
+                r_point: remainder.comm.point,
+            }
+            .into(),
+            CheckedSubtractionWithPublicParams {
+                proof: CheckedSubtraction::prove(&a, &b, &difference),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: difference.comm.point,
+            }
+            .into(),
+            CheckedAdditionWithPublicParams {
+                proof: CheckedAddition::prove(&a, &b, &sum),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+                c_point: sum.comm.point,
+            }
+            .into(),
+            RangeProofWithPublicParams {
+                proof: Range::prove(&signed, -10, 10),
+                x: signed.comm.point,
+                lo: -10,
+                hi: 10,
+            }
+            .into(),
+            ComparisonGeWithPublicParams {
+                proof: Comparison::prove_ge(&a, &b),
+                a_point: a.comm.point,
+                b_point: b.comm.point,
+            }
+            .into(),
+            (
+                CompactComparisonProofWithPublicParams {
+                    proof: Comparison::prove_compact(
+                        &Comparison::from_opening(&small),
+                        BitWidth::Bits8,
+                    ),
+                    x: small.comm.point,
+                },
+                BitWidth::Bits8,
+            )
+                .into(),
+            (
+                AggregatedComparisonProofWithPublicParams {
+                    proof: Comparison::prove_aggregated(
+                        &[Comparison::from_opening(&a), Comparison::from_opening(&b)],
+                        BitWidth::Bits16,
+                    ),
+                    x_vec: vec![a.comm.point, b.comm.point],
+                },
+                BitWidth::Bits16,
+            )
+                .into(),
+        ]
+    }
+
+    #[test]
+    fn verify_any_test() {
+        for (i, envelope) in envelopes().into_iter().enumerate() {
+            let bytes = envelope.to_bytes();
+            assert_eq!(bytes[..3], [MAGIC, VERSION, i as u8 + 1]);
+            assert_eq!(AnyProof::from_bytes(&bytes), Ok(envelope));
+            assert!(verify_any(&bytes));
+        }
+    }
+
+    #[test]
+    fn verify_any_with_context_test() {
+        let a = CommittedValue::commit(30);
+        let b = CommittedValue::commit(6);
+        let sum = Addition::commit_c_witness(36, a.secret.secret, b.secret.secret);
+        let bytes = AnyProof::from(AdditionProofWithPublicParams {
+            proof: Addition::prove_with_context(&a, &b, &sum, b"tx-42"),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: sum.comm.point,
+        })
+        .to_bytes();
+
+        assert!(verify_any_with_context(&bytes, b"tx-42"));
+        assert!(!verify_any_with_context(&bytes, b"tx-43"));
+        // the default is the empty context
+        assert!(!verify_any(&bytes));
+    }
+
+    #[test]
+    fn width_byte_test() {
+        let mut bytes = envelopes().remove(11).to_bytes();
+        assert_eq!(bytes[2], 12);
+        assert!(verify_any(&bytes));
+
+        // a width the proof was not made for
+        bytes[3] = 16;
+        assert!(!verify_any(&bytes));
+        bytes[3] = 7;
+        assert_eq!(try_verify_any(&bytes), Err(ProofError::InvalidEncoding));
+    }
+
+    #[test]
+    fn envelope_error_test() {
+        let bytes = envelopes().remove(0).to_bytes();
+
+        let mut tampered = bytes.clone();
+        tampered[0] ^= 1;
+        assert_eq!(try_verify_any(&tampered), Err(ProofError::InvalidEncoding));
+
+        let mut tampered = bytes.clone();
+        tampered[1] = VERSION + 1;
+        assert_eq!(
+            try_verify_any(&tampered),
+            Err(ProofError::UnsupportedVersion)
+        );
+
+        let mut tampered = bytes.clone();
+        tampered[2] = 0;
+        assert_eq!(try_verify_any(&tampered), Err(ProofError::InvalidEncoding));
+
+        // an addition proof relabelled as a subtraction proof
+        let mut tampered = bytes.clone();
+        tampered[2] = 2;
+        assert!(!verify_any(&tampered));
+
+        assert_eq!(
+            try_verify_any(&bytes[..2]),
+            Err(ProofError::InvalidEncoding)
+        );
+        assert_eq!(
+            try_verify_any(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,753 @@
+use crate::codec::{decompress, decompress_all, Reader, Writer};
+use crate::error::ProofError;
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{Identity, IsIdentity, MultiscalarMul, VartimeMultiscalarMul},
+};
+use rand_core::{CryptoRng, RngCore};
+use sha3::Sha3_512;
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
+
+/// Bulletproofs range proof that [v] opens to 0 <= v < 2^n, with an inner
+/// product argument in place of one commitment per bit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactComparisonProof {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub a: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub s: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub t1: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub t2: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub t_x: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub t_x_blinding: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub e_blinding: Scalar,
+    pub ipp: InnerProductProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactComparisonProofWithPublicParams {
+    pub proof: CompactComparisonProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub x: RistrettoPoint,
+}
+
+/// one proof that every commitment in x_vec opens to a value in [0, 2^n)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregatedComparisonProofWithPublicParams {
+    pub proof: CompactComparisonProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::points"))]
+    pub x_vec: Vec<RistrettoPoint>,
+}
+
+/// <a, b> = c for committed vectors a, b, log2(n) rounds of (L, R)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InnerProductProof {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed_points"))]
+    pub l_vec: Vec<CompressedRistretto>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed_points"))]
+    pub r_vec: Vec<CompressedRistretto>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub a: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub b: Scalar,
+}
+
+impl CompactComparisonProof {
+    /// canonical encoding, the (L, R) rounds prefixed with their count
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer
+            .compressed(&self.a)
+            .compressed(&self.s)
+            .compressed(&self.t1)
+            .compressed(&self.t2)
+            .scalar(&self.t_x)
+            .scalar(&self.t_x_blinding)
+            .scalar(&self.e_blinding);
+        self.ipp.write(writer);
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, ProofError> {
+        Ok(Self {
+            a: reader.compressed()?,
+            s: reader.compressed()?,
+            t1: reader.compressed()?,
+            t2: reader.compressed()?,
+            t_x: reader.scalar()?,
+            t_x_blinding: reader.scalar()?,
+            e_blinding: reader.scalar()?,
+            ipp: InnerProductProof::read(reader)?,
+        })
+    }
+}
+
+impl InnerProductProof {
+    fn write(&self, writer: &mut Writer) {
+        writer
+            .compressed_points(&self.l_vec)
+            .compressed_points(&self.r_vec)
+            .scalar(&self.a)
+            .scalar(&self.b);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ProofError> {
+        Ok(Self {
+            l_vec: reader.compressed_points()?,
+            r_vec: reader.compressed_points()?,
+            a: reader.scalar()?,
+            b: reader.scalar()?,
+        })
+    }
+}
+
+/// prove v in [0, 2^n) for [v] = v·G1 + gamma·G2, n a power of two
+pub(crate) fn prove_range<R: RngCore + CryptoRng>(
+    v: u64,
+    gamma: Scalar,
+    n: usize,
+    context: &[u8],
+    rng: &mut R,
+) -> CompactComparisonProof {
+    prove_aggregated_range(&[v], &[gamma], n, context, rng)
+}
+
+pub(crate) fn verify_range(
+    proof: CompactComparisonProofWithPublicParams,
+    n: usize,
+    context: &[u8],
+) -> Result<(), ProofError> {
+    let CompactComparisonProofWithPublicParams { proof, x } = proof;
+
+    verify_aggregated_range(
+        AggregatedComparisonProofWithPublicParams {
+            proof,
+            x_vec: vec![x],
+        },
+        n,
+        context,
+    )
+}
+
+/// prove every v_j in [0, 2^n) at once; m is padded to a power of two with
+/// commitments to zero under a zero blinding, i.e. the identity
+pub(crate) fn prove_aggregated_range<R: RngCore + CryptoRng>(
+    values: &[u64],
+    gammas: &[Scalar],
+    n: usize,
+    context: &[u8],
+    rng: &mut R,
+) -> CompactComparisonProof {
+    let m = values.len().next_power_of_two();
+    let nm = n * m;
+    let g_vec = generators(b"G", nm);
+    let h_vec = generators(b"H", nm);
+
+    let mut v_vec = values.to_vec();
+    let mut gamma_vec = gammas.to_vec();
+    v_vec.resize(m, 0);
+    gamma_vec.resize(m, Scalar::zero());
+    let v_points: Vec<RistrettoPoint> = v_vec
+        .iter()
+        .zip(gamma_vec.iter())
+        .map(|(v, gamma)| {
+            RistrettoPoint::multiscalar_mul(
+                &[Scalar::from(*v), *gamma],
+                &[*BASEPOINT_G1, *BASEPOINT_G2],
+            )
+        })
+        .collect();
+
+    // a_L = bits of v_0 || bits of v_1 || ..., a_R = a_L - 1^nm
+    let a_l: Vec<Scalar> = (0..nm)
+        .map(|i| Scalar::from((v_vec[i / n] >> (i % n)) & 1))
+        .collect();
+    let a_r: Vec<Scalar> = a_l.iter().map(|bit| bit - Scalar::one()).collect();
+
+    let mut transcript = Transcript::new(Protocol::CompactComparison, context);
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", m as u64);
+    for v_point in &v_points {
+        transcript.append_point(b"V", v_point);
+    }
+
+    let mut rng = v_vec
+        .iter()
+        .zip(gamma_vec.iter())
+        .fold(transcript.build_rng(), |builder, (v, gamma)| {
+            builder
+                .rekey_with_witness_bytes(b"v", &v.to_le_bytes())
+                .rekey_with_witness_scalar(b"gamma", gamma)
+        })
+        .finalize(rng);
+
+    let alpha = random_scalar(&mut rng);
+    let a_point = RistrettoPoint::multiscalar_mul(
+        a_l.iter().chain(a_r.iter()).chain(std::iter::once(&alpha)),
+        g_vec
+            .iter()
+            .chain(h_vec.iter())
+            .chain(std::iter::once(&*BASEPOINT_G2)),
+    );
+
+    let s_l: Vec<Scalar> = (0..nm).map(|_| random_scalar(&mut rng)).collect();
+    let s_r: Vec<Scalar> = (0..nm).map(|_| random_scalar(&mut rng)).collect();
+    let rho = random_scalar(&mut rng);
+    let s_point = RistrettoPoint::multiscalar_mul(
+        s_l.iter().chain(s_r.iter()).chain(std::iter::once(&rho)),
+        g_vec
+            .iter()
+            .chain(h_vec.iter())
+            .chain(std::iter::once(&*BASEPOINT_G2)),
+    );
+
+    transcript.append_point(b"A", &a_point);
+    transcript.append_point(b"S", &s_point);
+    let y = transcript.challenge_scalar(b"y");
+    let z = transcript.challenge_scalar(b"z");
+
+    // l(X) = (a_L - z·1) + s_L·X
+    // r(X) = y^nm ∘ (a_R + z·1 + s_R·X) + Σ_j z^(2+j)·(0^jn || 2^n || 0)
+    let y_pows = powers(y, nm);
+    let two_pows = powers(Scalar::from(2u64), n);
+    let z_pows = powers(z, m + 2);
+    let l0: Vec<Scalar> = a_l.iter().map(|a| a - z).collect();
+    let l1 = s_l;
+    let r0: Vec<Scalar> = (0..nm)
+        .map(|i| y_pows[i] * (a_r[i] + z) + z_pows[2 + i / n] * two_pows[i % n])
+        .collect();
+    let r1: Vec<Scalar> = (0..nm).map(|i| y_pows[i] * s_r[i]).collect();
+
+    let t1 = inner_product(&l0, &r1) + inner_product(&l1, &r0);
+    let t2 = inner_product(&l1, &r1);
+
+    let tau1 = random_scalar(&mut rng);
+    let tau2 = random_scalar(&mut rng);
+    let t1_point = RistrettoPoint::multiscalar_mul(&[t1, tau1], &[*BASEPOINT_G1, *BASEPOINT_G2]);
+    let t2_point = RistrettoPoint::multiscalar_mul(&[t2, tau2], &[*BASEPOINT_G1, *BASEPOINT_G2]);
+
+    transcript.append_point(b"T1", &t1_point);
+    transcript.append_point(b"T2", &t2_point);
+    let x = transcript.challenge_scalar(b"x");
+
+    let l_vec: Vec<Scalar> = (0..nm).map(|i| l0[i] + l1[i] * x).collect();
+    let r_vec: Vec<Scalar> = (0..nm).map(|i| r0[i] + r1[i] * x).collect();
+    let t_x = inner_product(&l_vec, &r_vec);
+    let mut t_x_blinding = tau2 * x * x + tau1 * x;
+    for (j, gamma) in gamma_vec.iter().enumerate() {
+        t_x_blinding += z_pows[2 + j] * gamma;
+    }
+    let e_blinding = alpha + rho * x;
+
+    transcript.append_scalar(b"t_x", &t_x);
+    transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+    transcript.append_scalar(b"e_blinding", &e_blinding);
+    let w = transcript.challenge_scalar(b"w");
+    let q_point = w * *BASEPOINT_G1;
+
+    // H'_i = y^-i · H_i turns <r, H'> into a commitment to r without the y^nm
+    let y_inv_pows = powers(y.invert(), nm);
+    let h_prime: Vec<RistrettoPoint> = (0..nm).map(|i| h_vec[i] * y_inv_pows[i]).collect();
+
+    let ipp = prove_inner_product(&mut transcript, &q_point, g_vec, h_prime, l_vec, r_vec);
+
+    CompactComparisonProof {
+        a: a_point.compress(),
+        s: s_point.compress(),
+        t1: t1_point.compress(),
+        t2: t2_point.compress(),
+        t_x,
+        t_x_blinding,
+        e_blinding,
+        ipp,
+    }
+}
+
+pub(crate) fn verify_aggregated_range(
+    proof: AggregatedComparisonProofWithPublicParams,
+    n: usize,
+    context: &[u8],
+) -> Result<(), ProofError> {
+    let AggregatedComparisonProofWithPublicParams {
+        proof:
+            CompactComparisonProof {
+                a: a_point,
+                s: s_point,
+                t1: t1_point,
+                t2: t2_point,
+                t_x,
+                t_x_blinding,
+                e_blinding,
+                ipp:
+                    InnerProductProof {
+                        l_vec: ipp_l_vec,
+                        r_vec: ipp_r_vec,
+                        a: ipp_a,
+                        b: ipp_b,
+                    },
+            },
+        x_vec: mut v_points,
+    } = proof;
+
+    if v_points.is_empty() || !n.is_power_of_two() {
+        return Err(ProofError::MalformedProof);
+    }
+    let m = v_points.len().next_power_of_two();
+    let nm = n * m;
+    v_points.resize(m, RistrettoPoint::identity());
+    if ipp_l_vec.len() != log2(nm) || ipp_r_vec.len() != log2(nm) {
+        return Err(ProofError::MalformedProof);
+    }
+    let a_point = decompress(&a_point)?;
+    let s_point = decompress(&s_point)?;
+    let t1_point = decompress(&t1_point)?;
+    let t2_point = decompress(&t2_point)?;
+    let ipp_l_vec = decompress_all(&ipp_l_vec)?;
+    let ipp_r_vec = decompress_all(&ipp_r_vec)?;
+    if [a_point, s_point, t1_point, t2_point]
+        .iter()
+        .chain(ipp_l_vec.iter())
+        .chain(ipp_r_vec.iter())
+        .any(|point| point.is_identity())
+    {
+        return Err(ProofError::DegeneratePoint);
+    }
+
+    let mut transcript = Transcript::new(Protocol::CompactComparison, context);
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", m as u64);
+    for v_point in &v_points {
+        transcript.append_point(b"V", v_point);
+    }
+    transcript.append_point(b"A", &a_point);
+    transcript.append_point(b"S", &s_point);
+    let y = transcript.challenge_scalar(b"y");
+    let z = transcript.challenge_scalar(b"z");
+
+    transcript.append_point(b"T1", &t1_point);
+    transcript.append_point(b"T2", &t2_point);
+    let x = transcript.challenge_scalar(b"x");
+
+    transcript.append_scalar(b"t_x", &t_x);
+    transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+    transcript.append_scalar(b"e_blinding", &e_blinding);
+    let w = transcript.challenge_scalar(b"w");
+
+    let mut u_vec = Vec::new();
+    for (l, r) in ipp_l_vec.iter().zip(ipp_r_vec.iter()) {
+        transcript.append_point(b"L", l);
+        transcript.append_point(b"R", r);
+        u_vec.push(transcript.challenge_scalar(b"u"));
+    }
+
+    let y_pows = powers(y, nm);
+    let two_pows = powers(Scalar::from(2u64), n);
+    let z_pows = powers(z, m + 3);
+    let z2 = z_pows[2];
+
+    /////////////verify 1/////////
+    // t_x·G1 + t_x_blinding·G2 = Σ_j z^(2+j)·V_j + delta(y, z)·G1 + x·T1 + x^2·T2
+    let delta = {
+        let two_sum = two_pows.iter().sum::<Scalar>();
+        let mut tmp = (z - z2) * y_pows.iter().sum::<Scalar>();
+        for j in 0..m {
+            tmp -= z_pows[3 + j] * two_sum;
+        }
+        tmp
+    };
+    let check_1 = RistrettoPoint::vartime_multiscalar_mul(
+        vec![t_x - delta, t_x_blinding, -x, -x * x]
+            .into_iter()
+            .chain((0..m).map(|j| -z_pows[2 + j])),
+        vec![&*BASEPOINT_G1, &*BASEPOINT_G2, &t1_point, &t2_point]
+            .into_iter()
+            .chain(v_points.iter()),
+    );
+
+    /////////////verify 2/////////
+    // A + x·S - z·<1, G> + <z·y^nm + Σ_j z^(2+j)·2^n_j, H'> - e_blinding·G2 + t_x·Q
+    //     = a·<s, G> + b·<s^-1, H'> + ab·Q - Σ (u_j^2·L_j + u_j^-2·R_j)
+    let s_vec = fold_scalars(&u_vec, nm);
+    let y_inv_pows = powers(y.invert(), nm);
+    let g_vec = generators(b"G", nm);
+    let h_vec = generators(b"H", nm);
+
+    let g_scalars = s_vec.iter().map(|s_i| ipp_a * s_i + z);
+    let h_scalars = (0..nm).map(|i| {
+        y_inv_pows[i] * (ipp_b * s_vec[nm - 1 - i] - z_pows[2 + i / n] * two_pows[i % n]) - z
+    });
+    let u_sq: Vec<Scalar> = u_vec.iter().map(|u| -(u * u)).collect();
+    let u_inv_sq: Vec<Scalar> = u_vec.iter().map(|u| -(u.invert() * u.invert())).collect();
+
+    let check_2 = RistrettoPoint::vartime_multiscalar_mul(
+        g_scalars
+            .chain(h_scalars)
+            .chain(u_sq)
+            .chain(u_inv_sq)
+            .chain(vec![
+                -Scalar::one(),
+                -x,
+                e_blinding,
+                w * (ipp_a * ipp_b - t_x),
+            ]),
+        g_vec
+            .iter()
+            .chain(h_vec.iter())
+            .chain(ipp_l_vec.iter())
+            .chain(ipp_r_vec.iter())
+            .chain(vec![&a_point, &s_point, &*BASEPOINT_G2, &*BASEPOINT_G1]),
+    );
+
+    if !check_1.is_identity() {
+        return Err(ProofError::FirstEquationFailed);
+    }
+    if !check_2.is_identity() {
+        return Err(ProofError::SecondEquationFailed);
+    }
+    Ok(())
+}
+
+fn prove_inner_product(
+    transcript: &mut Transcript,
+    q_point: &RistrettoPoint,
+    mut g_vec: Vec<RistrettoPoint>,
+    mut h_vec: Vec<RistrettoPoint>,
+    mut a_vec: Vec<Scalar>,
+    mut b_vec: Vec<Scalar>,
+) -> InnerProductProof {
+    let mut l_vec = Vec::new();
+    let mut r_vec = Vec::new();
+
+    let mut n = a_vec.len();
+    while n > 1 {
+        n /= 2;
+        let (a_lo, a_hi) = a_vec.split_at(n);
+        let (b_lo, b_hi) = b_vec.split_at(n);
+        let (g_lo, g_hi) = g_vec.split_at(n);
+        let (h_lo, h_hi) = h_vec.split_at(n);
+
+        let c_l = inner_product(a_lo, b_hi);
+        let c_r = inner_product(a_hi, b_lo);
+
+        let l_point = RistrettoPoint::multiscalar_mul(
+            a_lo.iter().chain(b_hi.iter()).chain(std::iter::once(&c_l)),
+            g_hi.iter()
+                .chain(h_lo.iter())
+                .chain(std::iter::once(q_point)),
+        );
+        let r_point = RistrettoPoint::multiscalar_mul(
+            a_hi.iter().chain(b_lo.iter()).chain(std::iter::once(&c_r)),
+            g_lo.iter()
+                .chain(h_hi.iter())
+                .chain(std::iter::once(q_point)),
+        );
+
+        transcript.append_point(b"L", &l_point);
+        transcript.append_point(b"R", &r_point);
+        let u = transcript.challenge_scalar(b"u");
+        let u_inv = u.invert();
+
+        a_vec = (0..n).map(|i| a_lo[i] * u + a_hi[i] * u_inv).collect();
+        b_vec = (0..n).map(|i| b_lo[i] * u_inv + b_hi[i] * u).collect();
+        g_vec = (0..n).map(|i| g_lo[i] * u_inv + g_hi[i] * u).collect();
+        h_vec = (0..n).map(|i| h_lo[i] * u + h_hi[i] * u_inv).collect();
+
+        l_vec.push(l_point.compress());
+        r_vec.push(r_point.compress());
+    }
+
+    InnerProductProof {
+        l_vec,
+        r_vec,
+        a: a_vec[0],
+        b: b_vec[0],
+    }
+}
+
+/// s_i = Π_j u_j^(±1), + when bit (k - j) of i is set; folding G by the
+/// round challenges collapses it to <s, G>, folding H to <s^-1, H>,
+/// and s^-1 is s reversed
+fn fold_scalars(u_vec: &[Scalar], n: usize) -> Vec<Scalar> {
+    let k = u_vec.len();
+    (0..n)
+        .map(|i| {
+            let mut s_i = Scalar::one();
+            for (j, u) in u_vec.iter().enumerate() {
+                if (i >> (k - 1 - j)) & 1 == 1 {
+                    s_i *= u;
+                } else {
+                    s_i *= u.invert();
+                }
+            }
+            s_i
+        })
+        .collect()
+}
+
+/// nothing-up-my-sleeve vector generators, independent of G1, G2 and each other
+fn generators(label: &[u8], n: usize) -> Vec<RistrettoPoint> {
+    (0..n)
+        .map(|i| {
+            let mut input = b"SVMZK bulletproof generator ".to_vec();
+            input.extend_from_slice(label);
+            input.extend_from_slice(&(i as u64).to_le_bytes());
+            RistrettoPoint::hash_from_bytes::<Sha3_512>(&input)
+        })
+        .collect()
+}
+
+fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b.iter()).map(|(a_i, b_i)| a_i * b_i).sum()
+}
+
+fn powers(a: Scalar, n: usize) -> Vec<Scalar> {
+    let mut res = Vec::with_capacity(n);
+    let mut tmp = Scalar::one();
+    for _ in 0..n {
+        res.push(tmp);
+        tmp *= a;
+    }
+    res
+}
+
+fn log2(n: usize) -> usize {
+    n.trailing_zeros() as usize
+}
+
+#[cfg(test)]
+mod bulletproof_tests {
+    use super::*;
+    use rand::Rng;
+    use rand_core::OsRng;
+    use wedpr_l_crypto_zkp_utils::get_random_scalar;
+
+    #[test]
+    fn it_works() {
+        let gamma = get_random_scalar();
+        let v_point = RistrettoPoint::multiscalar_mul(
+            &[Scalar::from(209348u64), gamma],
+            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        );
+
+        let proof = prove_range(209348, gamma, 32, &[], &mut OsRng);
+
+        let result = verify_range(
+            CompactComparisonProofWithPublicParams { proof, x: v_point },
+            32,
+            &[],
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn random_range_test() {
+        for n in [8usize, 16, 32, 64].iter() {
+            for _ in 0..5 {
+                let v = rand::thread_rng().gen_range(0u64..=u64::MAX);
+                let gamma = get_random_scalar();
+                let v_point = RistrettoPoint::multiscalar_mul(
+                    &[Scalar::from(v), gamma],
+                    &[*BASEPOINT_G1, *BASEPOINT_G2],
+                );
+
+                let proof = prove_range(v, gamma, *n, &[], &mut OsRng);
+
+                let result = verify_range(
+                    CompactComparisonProofWithPublicParams { proof, x: v_point },
+                    *n,
+                    &[],
+                );
+                assert_eq!(result.is_ok(), *n == 64 || v < (1u64 << *n));
+            }
+        }
+    }
+
+    /// 4 points, 5 scalars, 2·log2(n) points for the inner product and the
+    /// two length prefixes: 680 bytes for 64-bit values
+    #[test]
+    fn proof_size_test() {
+        let proof = prove_range(u64::MAX, get_random_scalar(), 64, &[], &mut OsRng);
+        assert_eq!(proof.ipp.l_vec.len(), 6);
+        assert_eq!(proof.ipp.r_vec.len(), 6);
+        let size = proof.to_bytes().len();
+        assert!(size <= 700, "{} bytes", size);
+    }
+
+    #[test]
+    fn tampered_proof_test() {
+        let gamma = get_random_scalar();
+        let v_point = RistrettoPoint::multiscalar_mul(
+            &[Scalar::from(1000u64), gamma],
+            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        );
+        let proof = prove_range(1000, gamma, 16, &[], &mut OsRng);
+
+        let mut tampered = proof.clone();
+        tampered.t_x += Scalar::one();
+        assert_eq!(
+            verify_range(
+                CompactComparisonProofWithPublicParams {
+                    proof: tampered,
+                    x: v_point,
+                },
+                16,
+                &[],
+            ),
+            Err(ProofError::FirstEquationFailed)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.ipp.a += Scalar::one();
+        assert_eq!(
+            verify_range(
+                CompactComparisonProofWithPublicParams {
+                    proof: tampered,
+                    x: v_point,
+                },
+                16,
+                &[],
+            ),
+            Err(ProofError::SecondEquationFailed)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.ipp.l_vec.swap(0, 1);
+        assert_eq!(
+            verify_range(
+                CompactComparisonProofWithPublicParams {
+                    proof: tampered,
+                    x: v_point,
+                },
+                16,
+                &[],
+            ),
+            Err(ProofError::SecondEquationFailed)
+        );
+
+        assert!(verify_range(
+            CompactComparisonProofWithPublicParams {
+                proof: proof.clone(),
+                x: v_point + *BASEPOINT_G1,
+            },
+            16,
+            &[],
+        )
+        .is_err());
+        assert_eq!(
+            verify_range(
+                CompactComparisonProofWithPublicParams { proof, x: v_point },
+                32,
+                &[],
+            ),
+            Err(ProofError::MalformedProof)
+        );
+    }
+
+    fn malformed_verify(
+        mutate: impl Fn(&mut AggregatedComparisonProofWithPublicParams),
+    ) -> Result<(), ProofError> {
+        let gammas = vec![get_random_scalar(), get_random_scalar()];
+        let values = vec![7u64, 1000];
+        let x_vec = values
+            .iter()
+            .zip(gammas.iter())
+            .map(|(v, gamma)| {
+                RistrettoPoint::multiscalar_mul(
+                    &[Scalar::from(*v), *gamma],
+                    &[*BASEPOINT_G1, *BASEPOINT_G2],
+                )
+            })
+            .collect();
+        let mut params = AggregatedComparisonProofWithPublicParams {
+            proof: prove_aggregated_range(&values, &gammas, 16, &[], &mut OsRng),
+            x_vec,
+        };
+        mutate(&mut params);
+
+        verify_aggregated_range(params, 16, &[])
+    }
+
+    #[test]
+    fn malformed_inner_product_test() {
+        assert_eq!(malformed_verify(|_| {}), Ok(()));
+
+        let res = malformed_verify(|params| {
+            params.proof.ipp.l_vec.pop();
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+
+        let res = malformed_verify(|params| params.proof.ipp.r_vec.clear());
+        assert_eq!(res, Err(ProofError::MalformedProof));
+
+        let res = malformed_verify(|params| {
+            let extra = params.proof.ipp.l_vec[0];
+            params.proof.ipp.l_vec.push(extra);
+            params.proof.ipp.r_vec.push(extra);
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn malformed_statement_test() {
+        let res = malformed_verify(|params| params.x_vec.clear());
+        assert_eq!(res, Err(ProofError::MalformedProof));
+
+        // 3 commitments pad to 4, so the 2-value proof is too short
+        let res = malformed_verify(|params| params.x_vec.push(*BASEPOINT_G1));
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn identity_points_test() {
+        let res = malformed_verify(|params| params.proof.a = CompressedRistretto::identity());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+
+        let res = malformed_verify(|params| params.proof.t2 = CompressedRistretto::identity());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+
+        let res =
+            malformed_verify(|params| params.proof.ipp.r_vec[0] = CompressedRistretto::identity());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let gammas = vec![get_random_scalar(), get_random_scalar()];
+        let proof = prove_aggregated_range(&[7, 1000], &gammas, 16, &[], &mut OsRng);
+
+        let bytes = proof.to_bytes();
+        // 4 points, 3 scalars, 5 rounds of (L, R) for 32 bits, a and b
+        assert_eq!(bytes.len(), 4 * 32 + 3 * 32 + 2 * (4 + 5 * 32) + 2 * 32);
+        assert_eq!(CompactComparisonProof::from_bytes(&bytes), Ok(proof));
+
+        let mut invalid = bytes.clone();
+        invalid[32] ^= 1;
+        assert_eq!(
+            CompactComparisonProof::from_bytes(&invalid),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::codec::{Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::add::{Addition, AdditionProof, AdditionProofWithPublicParams};
+use crate::protocol::bulletproof::{
+    prove_range, verify_range, CompactComparisonProof, CompactComparisonProofWithPublicParams,
+};
+use crate::protocol::commitment::CommittedValue;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+/// a + b = c with c < 2^64, a u64 addition that cannot overflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedAddition;
+
+/// the addition proof of a + b = c and a range proof of c in [0, 2^64)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckedAdditionProof {
+    pub sum: AdditionProof,
+    pub in_range: CompactComparisonProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckedAdditionWithPublicParams {
+    pub proof: CheckedAdditionProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub c_point: RistrettoPoint,
+}
+
+impl CheckedAdditionProof {
+    /// canonical encoding, the addition proof followed by the range proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.sum.write(&mut writer);
+        self.in_range.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            sum: AdditionProof::read(&mut reader)?,
+            in_range: CompactComparisonProof::read(&mut reader)?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl CheckedAddition {
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
+    }
+
+    pub fn commit_c_witness(value: u64, a_sec: Scalar, b_sec: Scalar) -> CommittedValue {
+        Addition::commit_c_witness(value, a_sec, b_sec)
+    }
+
+    pub fn prove(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+    ) -> CheckedAdditionProof {
+        Self::prove_with_context(a, b, c, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+    ) -> CheckedAdditionProof {
+        Self::prove_with_context_and_rng(a, b, c, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        rng: &mut R,
+    ) -> CheckedAdditionProof {
+        Self::prove_with_context_and_rng(a, b, c, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> CheckedAdditionProof {
+        let sub_context = checked_add_context(&a.comm.point, &b.comm.point, &c.comm.point, context);
+
+        let sum = Addition::prove_with_context_and_rng(a, b, c, &sub_context, rng);
+        let in_range = prove_range(c.secret.value, c.secret.secret, 64, &sub_context, rng);
+
+        CheckedAdditionProof { sum, in_range }
+    }
+
+    pub fn verify(proof: CheckedAdditionWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: CheckedAdditionWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: CheckedAdditionWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: CheckedAdditionWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let CheckedAdditionWithPublicParams {
+            proof: CheckedAdditionProof { sum, in_range },
+            a_point,
+            b_point,
+            c_point,
+        } = proof;
+
+        let sub_context = checked_add_context(&a_point, &b_point, &c_point, context);
+
+        Addition::try_verify_with_context(
+            AdditionProofWithPublicParams {
+                proof: sum,
+                a_point,
+                b_point,
+                c_point,
+            },
+            &sub_context,
+        )?;
+        verify_range(
+            CompactComparisonProofWithPublicParams {
+                proof: in_range,
+                x: c_point,
+            },
+            64,
+            &sub_context,
+        )
+    }
+}
+
+/// binds both sub-proofs to [a], [b] and [c] under the composite's label
+fn checked_add_context(
+    a_point: &RistrettoPoint,
+    b_point: &RistrettoPoint,
+    c_point: &RistrettoPoint,
+    context: &[u8],
+) -> [u8; 32] {
+    let mut transcript = Transcript::new(Protocol::CheckedAddition, context);
+    transcript.append_point(b"a", a_point);
+    transcript.append_point(b"b", b_point);
+    transcript.append_point(b"c", c_point);
+    transcript.sub_context()
+}
+
+#[cfg(test)]
+mod checked_addition_tests {
+    use super::*;
+    use crate::protocol::commitment::SignedCommittedValue;
+
+    #[test]
+    fn it_works() {
+        let test_data = vec![
+            (15u64, 18u64),
+            (0, 0),
+            (u64::MAX - 1, 1),
+            (1 << 63, (1 << 63) - 1),
+        ];
+        for (a, b) in test_data {
+            let a_comm_secret = CheckedAddition::commit(a);
+            let b_comm_secret = CheckedAddition::commit(b);
+            let c_comm_secret = CheckedAddition::commit_c_witness(
+                a + b,
+                a_comm_secret.secret.secret,
+                b_comm_secret.secret.secret,
+            );
+
+            let proof = CheckedAddition::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+            let result = CheckedAddition::try_verify(CheckedAdditionWithPublicParams {
+                proof,
+                a_point: a_comm_secret.comm.point,
+                b_point: b_comm_secret.comm.point,
+                c_point: c_comm_secret.comm.point,
+            });
+            assert_eq!(result, Ok(()), "{} + {}", a, b);
+        }
+    }
+
+    /// u64::MAX + 1 = 2^64 holds in the scalar field, but c < 2^64 does not
+    #[test]
+    fn overflow_test() {
+        use crate::protocol::commitment::Opening;
+
+        let a = Addition::commit_i128(u64::MAX as i128);
+        let b = Addition::commit_i128(1);
+        let c = Addition::commit_c_witness_i128(1 << 64, a.secret.secret, b.secret.secret);
+
+        assert!(Addition::verify(AdditionProofWithPublicParams {
+            proof: Addition::prove_signed(&a, &b, &c),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        // 2^64 wraps to 0 as a u64
+        let unsigned = |x: &SignedCommittedValue| CommittedValue {
+            comm: x.comm.clone(),
+            secret: Opening::new(x.secret.value as u64, x.secret.secret),
+        };
+        let proof = CheckedAddition::prove(&unsigned(&a), &unsigned(&b), &unsigned(&c));
+        let result = CheckedAddition::try_verify(CheckedAdditionWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        });
+        assert!(result.is_err());
+    }
+
+    /// the addition proof inside does not verify on its own
+    #[test]
+    fn lifted_sum_test() {
+        let a = CheckedAddition::commit(15);
+        let b = CheckedAddition::commit(18);
+        let c = CheckedAddition::commit_c_witness(33, a.secret.secret, b.secret.secret);
+        let proof = CheckedAddition::prove(&a, &b, &c);
+
+        assert!(!Addition::verify(AdditionProofWithPublicParams {
+            proof: proof.sum,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let a = CheckedAddition::commit(15);
+        let b = CheckedAddition::commit(18);
+        let c = CheckedAddition::commit_c_witness(33, a.secret.secret, b.secret.secret);
+        let proof = CheckedAddition::prove_with_context(&a, &b, &c, b"tx 1");
+
+        let bytes = proof.to_bytes();
+        assert_eq!(CheckedAdditionProof::from_bytes(&bytes), Ok(proof.clone()));
+        assert_eq!(
+            CheckedAdditionProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        let params = CheckedAdditionWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        };
+        assert!(CheckedAddition::verify_with_context(
+            params.clone(),
+            b"tx 1"
+        ));
+        assert!(!CheckedAddition::verify(params));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::codec::{Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::bulletproof::{
+    prove_range, verify_range, CompactComparisonProof, CompactComparisonProofWithPublicParams,
+};
+use crate::protocol::commitment::CommittedValue;
+use crate::protocol::sub::{Substraction, SubstractionProof, SubstractionWithPublicParams};
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, OsRng, RngCore};
+
+/// a - b = c with c >= 0, a subtraction that cannot underflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedSubtraction;
+
+/// the subtraction proof of a - b = c and a range proof of c in [0, 2^64);
+/// a negative c is −|c| in the scalar field, far beyond 2^64
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckedSubtractionProof {
+    pub difference: SubstractionProof,
+    pub non_negative: CompactComparisonProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckedSubtractionWithPublicParams {
+    pub proof: CheckedSubtractionProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub c_point: RistrettoPoint,
+}
+
+impl CheckedSubtractionProof {
+    /// canonical encoding, the subtraction proof followed by the range proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.difference.write(&mut writer);
+        self.non_negative.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            difference: SubstractionProof::read(&mut reader)?,
+            non_negative: CompactComparisonProof::read(&mut reader)?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl CheckedSubtraction {
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
+    }
+
+    pub fn commit_c_witness(value: u64, a_sec: Scalar, b_sec: Scalar) -> CommittedValue {
+        Substraction::commit_c_witness(value, a_sec, b_sec)
+    }
+
+    pub fn prove(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+    ) -> CheckedSubtractionProof {
+        Self::prove_with_context(a, b, c, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+    ) -> CheckedSubtractionProof {
+        Self::prove_with_context_and_rng(a, b, c, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        rng: &mut R,
+    ) -> CheckedSubtractionProof {
+        Self::prove_with_context_and_rng(a, b, c, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> CheckedSubtractionProof {
+        let sub_context = checked_sub_context(&a.comm.point, &b.comm.point, &c.comm.point, context);
+
+        let difference = Substraction::prove_with_context_and_rng(a, b, c, &sub_context, rng);
+        let non_negative = prove_range(c.secret.value, c.secret.secret, 64, &sub_context, rng);
+
+        CheckedSubtractionProof {
+            difference,
+            non_negative,
+        }
+    }
+
+    pub fn verify(proof: CheckedSubtractionWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: CheckedSubtractionWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: CheckedSubtractionWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: CheckedSubtractionWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let CheckedSubtractionWithPublicParams {
+            proof:
+                CheckedSubtractionProof {
+                    difference,
+                    non_negative,
+                },
+            a_point,
+            b_point,
+            c_point,
+        } = proof;
+
+        let sub_context = checked_sub_context(&a_point, &b_point, &c_point, context);
+
+        Substraction::try_verify_with_context(
+            SubstractionWithPublicParams {
+                proof: difference,
+                a_point,
+                b_point,
+                c_point,
+            },
+            &sub_context,
+        )?;
+        verify_range(
+            CompactComparisonProofWithPublicParams {
+                proof: non_negative,
+                x: c_point,
+            },
+            64,
+            &sub_context,
+        )
+    }
+}
+
+/// binds both sub-proofs to [a], [b] and [c] under the composite's label
+fn checked_sub_context(
+    a_point: &RistrettoPoint,
+    b_point: &RistrettoPoint,
+    c_point: &RistrettoPoint,
+    context: &[u8],
+) -> [u8; 32] {
+    let mut transcript = Transcript::new(Protocol::CheckedSubtraction, context);
+    transcript.append_point(b"a", a_point);
+    transcript.append_point(b"b", b_point);
+    transcript.append_point(b"c", c_point);
+    transcript.sub_context()
+}
+
+#[cfg(test)]
+mod checked_subtraction_tests {
+    use super::*;
+    use crate::protocol::commitment::SignedCommittedValue;
+
+    #[test]
+    fn it_works() {
+        let test_data = vec![(30u64, 18u64), (18, 18), (u64::MAX, 1), (7, 0)];
+        for (a, b) in test_data {
+            let a_comm_secret = CheckedSubtraction::commit(a);
+            let b_comm_secret = CheckedSubtraction::commit(b);
+            let c_comm_secret = CheckedSubtraction::commit_c_witness(
+                a - b,
+                a_comm_secret.secret.secret,
+                b_comm_secret.secret.secret,
+            );
+
+            let proof = CheckedSubtraction::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+            let result = CheckedSubtraction::try_verify(CheckedSubtractionWithPublicParams {
+                proof,
+                a_point: a_comm_secret.comm.point,
+                b_point: b_comm_secret.comm.point,
+                c_point: c_comm_secret.comm.point,
+            });
+            assert_eq!(result, Ok(()), "{} - {}", a, b);
+        }
+    }
+
+    /// 18 - 30 = -12 holds in the scalar field, but c >= 0 does not
+    #[test]
+    fn underflow_test() {
+        use crate::protocol::commitment::Opening;
+
+        let a = Substraction::commit_i64(18);
+        let b = Substraction::commit_i64(30);
+        let c = Substraction::commit_c_witness_i64(-12, a.secret.secret, b.secret.secret);
+
+        assert!(Substraction::verify(SubstractionWithPublicParams {
+            proof: Substraction::prove_signed(&a, &b, &c),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        // the closest a u64 comes to -12 is 2^64 - 12
+        let unsigned = |x: &SignedCommittedValue| CommittedValue {
+            comm: x.comm.clone(),
+            secret: Opening::new(x.secret.value as u64, x.secret.secret),
+        };
+        let proof = CheckedSubtraction::prove(&unsigned(&a), &unsigned(&b), &unsigned(&c));
+        let result = CheckedSubtraction::try_verify(CheckedSubtractionWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        });
+        assert!(result.is_err());
+    }
+
+    /// the subtraction proof inside does not verify on its own
+    #[test]
+    fn lifted_difference_test() {
+        let a = CheckedSubtraction::commit(30);
+        let b = CheckedSubtraction::commit(18);
+        let c = CheckedSubtraction::commit_c_witness(12, a.secret.secret, b.secret.secret);
+        let proof = CheckedSubtraction::prove(&a, &b, &c);
+
+        assert!(!Substraction::verify(SubstractionWithPublicParams {
+            proof: proof.difference,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let a = CheckedSubtraction::commit(30);
+        let b = CheckedSubtraction::commit(18);
+        let c = CheckedSubtraction::commit_c_witness(12, a.secret.secret, b.secret.secret);
+        let proof = CheckedSubtraction::prove_with_context(&a, &b, &c, b"tx 1");
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            CheckedSubtractionProof::from_bytes(&bytes),
+            Ok(proof.clone())
+        );
+        assert_eq!(
+            CheckedSubtractionProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        let params = CheckedSubtractionWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        };
+        assert!(CheckedSubtraction::verify_with_context(
+            params.clone(),
+            b"tx 1"
+        ));
+        assert!(!CheckedSubtraction::verify(params));
+    }
+}
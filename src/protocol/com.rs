@@ -1,52 +1,381 @@
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
-use wedpr_l_crypto_zkp_utils::{
-    get_random_scalar, hash_to_scalar, point_to_bytes, BASEPOINT_G1, BASEPOINT_G2,
+use crate::codec::{decompress_all, Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::bulletproof::{
+    prove_aggregated_range, prove_range, verify_aggregated_range, verify_range,
+    AggregatedComparisonProofWithPublicParams, CompactComparisonProof,
+    CompactComparisonProofWithPublicParams,
 };
+use crate::protocol::commitment::{CommittedValue, Opening};
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, MultiscalarMul},
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
+
+/// Bit commitments beyond this could make Σ 2^i · b_i wrap around the group
+/// order, so a negative x could pass as a sum of bits.
+const MAX_BITS: usize = 64;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Comparison {
     pub comm: ComparisonCommitment,
     pub secret: ComparisonSecret,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComparisonCommitment {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub point: RistrettoPoint,
-    pub b0: RistrettoPoint,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct ComparisonSecret {
     value: Vec<u8>,
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::points"))]
     bi_vec: Vec<RistrettoPoint>,
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::scalars"))]
     ri_vec: Vec<Scalar>,
 }
 
+/// The bit commitments B_i with x = Σ 2^i · B_i, and for every B_i an OR
+/// proof (Cramer–Damgård–Schoenmakers) that it opens to 0 or to 1: either
+/// B_i = r_i · G2 or B_i - G1 = r_i · G2. A_0i and A_1i are the announcements
+/// of the two branches, c_0i the challenge of the 0 branch (the 1 branch gets
+/// e - c_0i) and s_0i, s_1i their responses.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComparisonProof {
-    bi_point_vec: Vec<RistrettoPoint>,
-    d1: RistrettoPoint,
-    d2: RistrettoPoint,
-    u_circumflex: Scalar,
-    b_circumflex_vec: Vec<Scalar>,
-    r_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed_points"))]
+    pub bi_point_vec: Vec<CompressedRistretto>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed_points"))]
+    pub a0_point_vec: Vec<CompressedRistretto>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed_points"))]
+    pub a1_point_vec: Vec<CompressedRistretto>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalars"))]
+    pub c0_vec: Vec<Scalar>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalars"))]
+    pub s0_vec: Vec<Scalar>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalars"))]
+    pub s1_vec: Vec<Scalar>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComparisonProofWithPublicParams {
     pub proof: ComparisonProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub x: RistrettoPoint,
 }
 
+/// Number of bit commitments in a fixed-width proof, so the proof size does
+/// not depend on the committed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitWidth {
+    Bits8,
+    Bits16,
+    Bits32,
+    Bits64,
+}
+
+impl BitWidth {
+    pub fn bits(self) -> usize {
+        match self {
+            BitWidth::Bits8 => 8,
+            BitWidth::Bits16 => 16,
+            BitWidth::Bits32 => 32,
+            BitWidth::Bits64 => 64,
+        }
+    }
+}
+
+/// a >= b
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComparisonGeWithPublicParams {
+    pub proof: ComparisonProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub b_point: RistrettoPoint,
+}
+
+impl ComparisonProof {
+    /// canonical encoding, the bit commitments, announcements, challenges and
+    /// responses each prefixed with their count
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer
+            .compressed_points(&self.bi_point_vec)
+            .compressed_points(&self.a0_point_vec)
+            .compressed_points(&self.a1_point_vec)
+            .scalars(&self.c0_vec)
+            .scalars(&self.s0_vec)
+            .scalars(&self.s1_vec);
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, ProofError> {
+        Ok(Self {
+            bi_point_vec: reader.compressed_points()?,
+            a0_point_vec: reader.compressed_points()?,
+            a1_point_vec: reader.compressed_points()?,
+            c0_vec: reader.scalars()?,
+            s0_vec: reader.scalars()?,
+            s1_vec: reader.scalars()?,
+        })
+    }
+}
+
 impl Comparison {
     pub fn commit(value: i32) -> Self {
-        let decomposed = decompose_number(value);
+        Self::commit_with_rng(value, &mut OsRng)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: i32, rng: &mut R) -> Self {
+        let s = random_scalar(rng);
+
+        Self::commit_with_blinding_and_rng(value, s, rng)
+    }
+
+    /// commit to x as x·G1 + s·G2 under a blinding chosen by the caller, so
+    /// an existing commitment can be proven non-negative
+    pub fn commit_with_blinding(value: i32, s: Scalar) -> Self {
+        Self::commit_with_blinding_and_rng(value, s, &mut OsRng)
+    }
+
+    /// `rng` only draws the blindings of the bit commitments here
+    pub fn commit_with_blinding_and_rng<R: RngCore + CryptoRng>(
+        value: i32,
+        s: Scalar,
+        rng: &mut R,
+    ) -> Self {
+        let x = {
+            let mut tmp = Scalar::zero();
+            if value < 0 {
+                tmp = -Scalar::from(-value as u64);
+            } else {
+                tmp = Scalar::from(value as u64);
+            }
+            tmp
+        };
+
+        Self::commit_bits(x, s, decompose_number(value), rng)
+    }
+
+    /// commit to x with exactly `width` bit commitments, proving x >= 0
+    /// only succeeds for 0 <= x < 2^width
+    pub fn commit_with_width(value: i64, width: BitWidth) -> Self {
+        Self::commit_with_width_and_rng(value, width, &mut OsRng)
+    }
+
+    pub fn commit_with_width_and_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        width: BitWidth,
+        rng: &mut R,
+    ) -> Self {
+        let s = random_scalar(rng);
+
+        Self::commit_with_width_and_blinding_and_rng(value, width, s, rng)
+    }
+
+    pub fn commit_with_width_and_blinding(value: i64, width: BitWidth, s: Scalar) -> Self {
+        Self::commit_with_width_and_blinding_and_rng(value, width, s, &mut OsRng)
+    }
+
+    pub fn commit_with_width_and_blinding_and_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        width: BitWidth,
+        s: Scalar,
+        rng: &mut R,
+    ) -> Self {
+        let x = {
+            let mut tmp = Scalar::zero();
+            if value < 0 {
+                tmp = -Scalar::from(value.unsigned_abs());
+            } else {
+                tmp = Scalar::from(value as u64);
+            }
+            tmp
+        };
+
+        Self::commit_bits(x, s, decompose_with_width(value.unsigned_abs(), width), rng)
+    }
+
+    /// prove x >= 0 for a commitment made by any relation
+    pub fn from_opening(x: &CommittedValue) -> Self {
+        Self::from_opening_with_rng(x, &mut OsRng)
+    }
+
+    pub fn from_opening_with_rng<R: RngCore + CryptoRng>(x: &CommittedValue, rng: &mut R) -> Self {
+        let Opening { value, secret } = x.secret;
+
+        Self::commit_bits(Scalar::from(value), secret, decompose_u64(value), rng)
+    }
+
+    /// same as `from_opening`, with exactly `width` bit commitments
+    pub fn from_opening_with_width(x: &CommittedValue, width: BitWidth) -> Self {
+        Self::from_opening_with_width_and_rng(x, width, &mut OsRng)
+    }
+
+    pub fn from_opening_with_width_and_rng<R: RngCore + CryptoRng>(
+        x: &CommittedValue,
+        width: BitWidth,
+        rng: &mut R,
+    ) -> Self {
+        let Opening { value, secret } = x.secret;
+
+        Self::commit_bits(
+            Scalar::from(value),
+            secret,
+            decompose_with_width(value, width),
+            rng,
+        )
+    }
+
+    /// prove a >= b by proving [a] - [b] commits to a non-negative value
+    pub fn prove_ge(a: &CommittedValue, b: &CommittedValue) -> ComparisonProof {
+        Self::prove_ge_with_context(a, b, &[])
+    }
+
+    pub fn prove_ge_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        context: &[u8],
+    ) -> ComparisonProof {
+        Self::prove_ge_with_context_and_rng(a, b, context, &mut OsRng)
+    }
+
+    pub fn prove_ge_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        rng: &mut R,
+    ) -> ComparisonProof {
+        Self::prove_ge_with_context_and_rng(a, b, &[], rng)
+    }
+
+    pub fn prove_ge_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> ComparisonProof {
+        let Opening {
+            value: a_value,
+            secret: a_secret,
+        } = a.secret;
+        let Opening {
+            value: b_value,
+            secret: b_secret,
+        } = b.secret;
+
+        let x = Scalar::from(a_value) - Scalar::from(b_value);
+        let s = a_secret - b_secret;
+        let decomposed = if a_value >= b_value {
+            decompose_u64(a_value - b_value)
+        } else {
+            decompose_u64(b_value - a_value)
+        };
+
+        let x = Self::commit_bits(x, s, decomposed, rng);
+
+        Self::prove_with_context_and_rng(&x, context, rng)
+    }
+
+    /// prove a >= b with exactly `width` bit commitments, which only succeeds
+    /// for 0 <= a - b < 2^width
+    pub fn prove_ge_with_width(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        width: BitWidth,
+    ) -> ComparisonProof {
+        Self::prove_ge_with_width_with_context(a, b, width, &[])
+    }
+
+    pub fn prove_ge_with_width_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        width: BitWidth,
+        context: &[u8],
+    ) -> ComparisonProof {
+        Self::prove_ge_with_width_with_context_and_rng(a, b, width, context, &mut OsRng)
+    }
+
+    pub fn prove_ge_with_width_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        width: BitWidth,
+        rng: &mut R,
+    ) -> ComparisonProof {
+        Self::prove_ge_with_width_with_context_and_rng(a, b, width, &[], rng)
+    }
+
+    pub fn prove_ge_with_width_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        width: BitWidth,
+        context: &[u8],
+        rng: &mut R,
+    ) -> ComparisonProof {
+        let Opening {
+            value: a_value,
+            secret: a_secret,
+        } = a.secret;
+        let Opening {
+            value: b_value,
+            secret: b_secret,
+        } = b.secret;
+
+        let x = Scalar::from(a_value) - Scalar::from(b_value);
+        let s = a_secret - b_secret;
+        let decomposed = if a_value >= b_value {
+            decompose_with_width(a_value - b_value, width)
+        } else {
+            decompose_with_width(b_value - a_value, width)
+        };
+
+        let x = Self::commit_bits(x, s, decomposed, rng);
+
+        Self::prove_with_context_and_rng(&x, context, rng)
+    }
+
+    /// commit to the bits of x so that [x] = Σ 2^i · B_i, B_0 absorbing
+    /// whatever the higher bits leave over
+    pub(crate) fn commit_bits<R: RngCore + CryptoRng>(
+        x: Scalar,
+        s: Scalar,
+        decomposed: Vec<u8>,
+        rng: &mut R,
+    ) -> Self {
         let len = decomposed.len();
         let (mut b_vec, mut r_vec) = {
             let mut b_vec_tmp = Vec::new();
             let mut r_vec_tmp = Vec::new();
             decomposed.iter().for_each(|each_bit| {
-                let secret = get_random_scalar();
+                let secret = random_scalar(rng);
                 let b_commitment_point = RistrettoPoint::multiscalar_mul(
                     &[Scalar::from(*each_bit), secret],
                     &[*BASEPOINT_G1, *BASEPOINT_G2],
@@ -57,30 +386,20 @@ impl Comparison {
             (b_vec_tmp, r_vec_tmp)
         };
 
-        let x = {
-            let mut tmp = Scalar::zero();
-            if value < 0 {
-                tmp = -Scalar::from(-value as u64);
-            } else {
-                tmp = Scalar::from(value as u64);
-            }
-            tmp
-        };
-        let s = get_random_scalar();
         let x_commitment_point =
             RistrettoPoint::multiscalar_mul(&[x, s], &[*BASEPOINT_G1, *BASEPOINT_G2]);
 
         if len > 1 {
             let mut b_sigma = Scalar::from(2_u64) * b_vec[1];
-            for i in 2..=len - 1 {
-                b_sigma += Scalar::from(2u64.pow(i as u32)) * b_vec[i];
+            for (i, b_i) in b_vec.iter().enumerate().skip(2) {
+                b_sigma += Scalar::from(2u64.pow(i as u32)) * b_i;
             }
             let b0 = x_commitment_point - b_sigma;
             b_vec[0] = b0;
 
             let mut r_sigma = Scalar::from(2_u64) * r_vec[1];
-            for i in 2..=len - 1 {
-                r_sigma += Scalar::from(2u64.pow(i as u32)) * r_vec[i];
+            for (i, r_i) in r_vec.iter().enumerate().skip(2) {
+                r_sigma += Scalar::from(2u64.pow(i as u32)) * r_i;
             }
             let r0 = s - r_sigma;
             r_vec[0] = r0;
@@ -92,7 +411,6 @@ impl Comparison {
         Self {
             comm: ComparisonCommitment {
                 point: x_commitment_point,
-                b0: RistrettoPoint::default(),
             },
             secret: ComparisonSecret {
                 value: decomposed,
@@ -103,12 +421,29 @@ impl Comparison {
     }
 
     pub fn prove(a: &Comparison) -> ComparisonProof {
+        Self::prove_with_context(a, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(a: &Comparison, context: &[u8]) -> ComparisonProof {
+        Self::prove_with_context_and_rng(a, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(a: &Comparison, rng: &mut R) -> ComparisonProof {
+        Self::prove_with_context_and_rng(a, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &Comparison,
+        context: &[u8],
+        rng: &mut R,
+    ) -> ComparisonProof {
         let Comparison {
-            comm:
-                ComparisonCommitment {
-                    point: x_commitment_point,
-                    b0: _b0,
-                },
+            comm: ComparisonCommitment {
+                point: x_commitment_point,
+            },
             secret:
                 ComparisonSecret {
                     value: decomposed,
@@ -117,137 +452,448 @@ impl Comparison {
                 },
         } = a;
 
-        let len = decomposed.len();
+        let mut transcript = Transcript::new(Protocol::Comparison, context);
+        transcript.append_point(b"x", x_commitment_point);
+        transcript.append_u64(b"n", b_vec.len() as u64);
+        for b_i in b_vec {
+            transcript.append_point(b"B_i", b_i);
+        }
 
-        let b_i_prime_1 = get_random_scalar();
-        let mut b_i_prime_vec = Vec::new();
-        b_i_prime_vec.push(b_i_prime_1);
-        let mut b_sigma_prime = b_i_prime_1;
-        let mut bi_b_sigma_prime = b_i_prime_1 * Scalar::from(decomposed[0]);
-        for i in 1..=len - 1 {
-            let b_i_prime = get_random_scalar();
-            b_i_prime_vec.push(b_i_prime);
-            b_sigma_prime += b_i_prime;
-            bi_b_sigma_prime += b_i_prime * Scalar::from(decomposed[i]);
-        }
-
-        let r_prime = get_random_scalar();
-        let d1 = RistrettoPoint::multiscalar_mul(
-            &[b_sigma_prime, r_prime],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+        let mut rng = decomposed
+            .iter()
+            .zip(r_vec.iter())
+            .fold(transcript.build_rng(), |builder, (bit, r_i)| {
+                builder
+                    .rekey_with_witness_bytes(b"b_i", &[*bit])
+                    .rekey_with_witness_scalar(b"r_i", r_i)
+            })
+            .finalize(rng);
 
-        let u_prime = get_random_scalar();
-        let d2 = RistrettoPoint::multiscalar_mul(
-            &[bi_b_sigma_prime, u_prime],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+        // the branch the bit takes is proven with nonce k, the other one is
+        // simulated from a challenge c' and response s' picked up front
+        let mut nonces = Vec::new();
+        let mut a0_vec = Vec::new();
+        let mut a1_vec = Vec::new();
+        for (bit, b_i) in decomposed.iter().zip(b_vec.iter()) {
+            let k = random_scalar(&mut rng);
+            let c_simulated = random_scalar(&mut rng);
+            let s_simulated = random_scalar(&mut rng);
+
+            let a_real = k * *BASEPOINT_G2;
+            let (a0, a1) = if *bit == 0 {
+                let a1 = RistrettoPoint::multiscalar_mul(
+                    &[s_simulated, -c_simulated],
+                    &[*BASEPOINT_G2, b_i - *BASEPOINT_G1],
+                );
+                (a_real, a1)
+            } else {
+                let a0 = RistrettoPoint::multiscalar_mul(
+                    &[s_simulated, -c_simulated],
+                    &[*BASEPOINT_G2, *b_i],
+                );
+                (a0, a_real)
+            };
+            transcript.append_point(b"A_0i", &a0);
+            transcript.append_point(b"A_1i", &a1);
+            a0_vec.push(a0);
+            a1_vec.push(a1);
+            nonces.push((k, c_simulated, s_simulated));
+        }
+
+        let e = transcript.challenge_scalar(b"e");
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(x_commitment_point));
-        for i in 0..=len - 1 {
-            hash_vec.append(&mut point_to_bytes(&b_vec[i]));
-        }
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
-
-        let e = hash_to_scalar(&hash_vec);
-
-        let (u_circumflex, r_circumflex, b_circumflex_vec) = {
-            let mut u_circumflex_tmp = u_prime;
-            let mut r_circumflex_tmp = r_prime;
-            let mut b_circumflex_tmp = Vec::new();
-            for i in 0..=len - 1 {
-                let bj_each = Scalar::from(decomposed[i]) * pow_scalar(e, i) + b_i_prime_vec[i];
-                b_circumflex_tmp.push(bj_each);
-                u_circumflex_tmp += (pow_scalar(e, i) - (bj_each)) * r_vec[i];
-                r_circumflex_tmp += r_vec[i] * pow_scalar(e, i);
+        let mut c0_vec = Vec::new();
+        let mut s0_vec = Vec::new();
+        let mut s1_vec = Vec::new();
+        for ((bit, r_i), (k, c_simulated, s_simulated)) in
+            decomposed.iter().zip(r_vec.iter()).zip(nonces)
+        {
+            let c_real = e - c_simulated;
+            let s_real = k + c_real * r_i;
+            if *bit == 0 {
+                c0_vec.push(c_real);
+                s0_vec.push(s_real);
+                s1_vec.push(s_simulated);
+            } else {
+                c0_vec.push(c_simulated);
+                s0_vec.push(s_simulated);
+                s1_vec.push(s_real);
             }
-            (u_circumflex_tmp, r_circumflex_tmp, b_circumflex_tmp)
-        };
+        }
 
         ComparisonProof {
-            bi_point_vec: b_vec.clone(),
-            d1,
-            d2,
-            u_circumflex,
-            b_circumflex_vec,
-            r_circumflex,
+            bi_point_vec: b_vec.iter().map(|b_i| b_i.compress()).collect(),
+            a0_point_vec: a0_vec.iter().map(|a0| a0.compress()).collect(),
+            a1_point_vec: a1_vec.iter().map(|a1| a1.compress()).collect(),
+            c0_vec,
+            s0_vec,
+            s1_vec,
         }
     }
 
+    /// prove x >= 0 with the inner product argument backend, whose size grows
+    /// with log2(width) instead of width; only succeeds for 0 <= x < 2^width
+    pub fn prove_compact(a: &Comparison, width: BitWidth) -> CompactComparisonProof {
+        Self::prove_compact_with_context(a, width, &[])
+    }
+
+    pub fn prove_compact_with_context(
+        a: &Comparison,
+        width: BitWidth,
+        context: &[u8],
+    ) -> CompactComparisonProof {
+        Self::prove_compact_with_context_and_rng(a, width, context, &mut OsRng)
+    }
+
+    pub fn prove_compact_with_rng<R: RngCore + CryptoRng>(
+        a: &Comparison,
+        width: BitWidth,
+        rng: &mut R,
+    ) -> CompactComparisonProof {
+        Self::prove_compact_with_context_and_rng(a, width, &[], rng)
+    }
+
+    pub fn prove_compact_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &Comparison,
+        width: BitWidth,
+        context: &[u8],
+        rng: &mut R,
+    ) -> CompactComparisonProof {
+        let (v, s) = a.compact_opening(width);
+
+        prove_range(v, s, width.bits(), context, rng)
+    }
+
+    /// prove x_j >= 0 for every commitment in one proof, of size
+    /// O(log2(width · len)) instead of len separate proofs
+    pub fn prove_aggregated(a: &[Comparison], width: BitWidth) -> CompactComparisonProof {
+        Self::prove_aggregated_with_context(a, width, &[])
+    }
+
+    pub fn prove_aggregated_with_context(
+        a: &[Comparison],
+        width: BitWidth,
+        context: &[u8],
+    ) -> CompactComparisonProof {
+        Self::prove_aggregated_with_context_and_rng(a, width, context, &mut OsRng)
+    }
+
+    pub fn prove_aggregated_with_rng<R: RngCore + CryptoRng>(
+        a: &[Comparison],
+        width: BitWidth,
+        rng: &mut R,
+    ) -> CompactComparisonProof {
+        Self::prove_aggregated_with_context_and_rng(a, width, &[], rng)
+    }
+
+    pub fn prove_aggregated_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &[Comparison],
+        width: BitWidth,
+        context: &[u8],
+        rng: &mut R,
+    ) -> CompactComparisonProof {
+        let (v_vec, s_vec): (Vec<u64>, Vec<Scalar>) =
+            a.iter().map(|each| each.compact_opening(width)).unzip();
+
+        prove_aggregated_range(&v_vec, &s_vec, width.bits(), context, rng)
+    }
+
+    fn compact_opening(&self, width: BitWidth) -> (u64, Scalar) {
+        let ComparisonSecret {
+            value: decomposed,
+            bi_vec: _b_vec,
+            ri_vec: r_vec,
+        } = &self.secret;
+
+        // x = Σ 2^i · b_i and s = Σ 2^i · r_i by construction of B_0
+        let mut v = 0u64;
+        for (i, bit) in decomposed.iter().enumerate().take(width.bits()) {
+            v |= (*bit as u64) << i;
+        }
+        let mut s = Scalar::zero();
+        for (i, r_i) in r_vec.iter().enumerate() {
+            s += pow_scalar(Scalar::from(2u64), i) * r_i;
+        }
+        (v, s)
+    }
+
     pub fn verify(proof: ComparisonProofWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: ComparisonProofWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: ComparisonProofWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: ComparisonProofWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
         let ComparisonProofWithPublicParams {
             proof:
                 ComparisonProof {
                     bi_point_vec: b_vec,
-                    d1,
-                    d2,
-                    u_circumflex,
-                    b_circumflex_vec,
-                    r_circumflex,
+                    a0_point_vec: a0_vec,
+                    a1_point_vec: a1_vec,
+                    c0_vec,
+                    s0_vec,
+                    s1_vec,
                 },
             x: x_commitment_point,
         } = proof;
 
         let len = b_vec.len();
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(&x_commitment_point));
-        for i in 0..=len - 1 {
-            hash_vec.append(&mut point_to_bytes(&b_vec[i]));
-        }
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
-
-        let e = hash_to_scalar(&hash_vec);
-
-        /////////////verify 1/////////
-        let left_1 = {
-            let mut tmp = d1;
-            for i in 0..=len - 1 {
-                tmp += b_vec[i] * pow_scalar(e, i);
-            }
-            tmp
-        };
+        if len == 0
+            || len > MAX_BITS
+            || a0_vec.len() != len
+            || a1_vec.len() != len
+            || c0_vec.len() != len
+            || s0_vec.len() != len
+            || s1_vec.len() != len
+        {
+            return Err(ProofError::MalformedProof);
+        }
+        let b_vec = decompress_all(&b_vec)?;
+        let a0_vec = decompress_all(&a0_vec)?;
+        let a1_vec = decompress_all(&a1_vec)?;
+        if b_vec
+            .iter()
+            .chain(a0_vec.iter())
+            .chain(a1_vec.iter())
+            .any(|point| point.is_identity())
+        {
+            return Err(ProofError::DegeneratePoint);
+        }
 
-        let bj_res = {
-            let mut tmp = Scalar::zero();
-            for i in 0..=len - 1 {
-                tmp += b_circumflex_vec[i];
+        let mut transcript = Transcript::new(Protocol::Comparison, context);
+        transcript.append_point(b"x", &x_commitment_point);
+        transcript.append_u64(b"n", len as u64);
+        for b_i in &b_vec {
+            transcript.append_point(b"B_i", b_i);
+        }
+        for (a0, a1) in a0_vec.iter().zip(a1_vec.iter()) {
+            transcript.append_point(b"A_0i", a0);
+            transcript.append_point(b"A_1i", a1);
+        }
+
+        let e = transcript.challenge_scalar(b"e");
+
+        for i in 0..len {
+            /////////////verify 1/////////
+            // s_0i · G2 = A_0i + c_0i · B_i, B_i opens to 0
+            let left_1 = s0_vec[i] * *BASEPOINT_G2;
+            let right_1 = a0_vec[i] + c0_vec[i] * b_vec[i];
+            if left_1 != right_1 {
+                return Err(ProofError::FirstEquationFailed);
             }
-            tmp
-        };
 
-        let right_1 = RistrettoPoint::multiscalar_mul(
-            &[bj_res, r_circumflex],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
-        /////////////verify 2/////////
-        let left_2 = {
-            let mut tmp = d2;
-            for i in 0..=len - 1 {
-                tmp += b_vec[i] * (pow_scalar(e, i) - b_circumflex_vec[i]);
+            /////////////verify 2/////////
+            // s_1i · G2 = A_1i + (e - c_0i) · (B_i - G1), B_i opens to 1
+            let left_2 = s1_vec[i] * *BASEPOINT_G2;
+            let right_2 = a1_vec[i] + (e - c0_vec[i]) * (b_vec[i] - *BASEPOINT_G1);
+            if left_2 != right_2 {
+                return Err(ProofError::SecondEquationFailed);
             }
-            tmp
-        };
+        }
 
-        let right_2 = RistrettoPoint::multiscalar_mul(
-            &[Scalar::zero(), u_circumflex],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        /////////////verify 3/////////
+        // x = Σ 2^i · B_i, otherwise the bits say nothing about x
+        let x_cal = RistrettoPoint::multiscalar_mul(
+            (0..len).map(|i| pow_scalar(Scalar::from(2u64), i)),
+            &b_vec,
         );
+        if x_cal != x_commitment_point {
+            return Err(ProofError::BitDecompositionMismatch);
+        }
+        Ok(())
+    }
 
-        left_1 == right_1 && left_2 == right_2
+    pub fn verify_compact(proof: CompactComparisonProofWithPublicParams, width: BitWidth) -> bool {
+        Self::try_verify_compact(proof, width).is_ok()
+    }
+
+    pub fn try_verify_compact(
+        proof: CompactComparisonProofWithPublicParams,
+        width: BitWidth,
+    ) -> Result<(), ProofError> {
+        Self::try_verify_compact_with_context(proof, width, &[])
     }
-}
 
-pub fn decompose_number(mut input: i32) -> Vec<u8> {
-    if input < 0 {
-        input = -input;
+    pub fn verify_compact_with_context(
+        proof: CompactComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_compact_with_context(proof, width, context).is_ok()
     }
+
+    pub fn try_verify_compact_with_context(
+        proof: CompactComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        verify_range(proof, width.bits(), context)
+    }
+
+    pub fn verify_aggregated(
+        proof: AggregatedComparisonProofWithPublicParams,
+        width: BitWidth,
+    ) -> bool {
+        Self::try_verify_aggregated(proof, width).is_ok()
+    }
+
+    pub fn try_verify_aggregated(
+        proof: AggregatedComparisonProofWithPublicParams,
+        width: BitWidth,
+    ) -> Result<(), ProofError> {
+        Self::try_verify_aggregated_with_context(proof, width, &[])
+    }
+
+    pub fn verify_aggregated_with_context(
+        proof: AggregatedComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_aggregated_with_context(proof, width, context).is_ok()
+    }
+
+    pub fn try_verify_aggregated_with_context(
+        proof: AggregatedComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        verify_aggregated_range(proof, width.bits(), context)
+    }
+
+    pub fn verify_ge(proof: ComparisonGeWithPublicParams) -> bool {
+        Self::try_verify_ge(proof).is_ok()
+    }
+
+    pub fn try_verify_ge(proof: ComparisonGeWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_ge_with_context(proof, &[])
+    }
+
+    pub fn verify_ge_with_context(proof: ComparisonGeWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_ge_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_ge_with_context(
+        proof: ComparisonGeWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let ComparisonGeWithPublicParams {
+            proof,
+            a_point,
+            b_point,
+        } = proof;
+
+        Self::try_verify_with_context(
+            ComparisonProofWithPublicParams {
+                proof,
+                x: a_point - b_point,
+            },
+            context,
+        )
+    }
+
+    /// verify x >= 0, rejecting proofs that do not carry exactly `width` bits
+    pub fn verify_with_width(proof: ComparisonProofWithPublicParams, width: BitWidth) -> bool {
+        Self::try_verify_with_width(proof, width).is_ok()
+    }
+
+    pub fn try_verify_with_width(
+        proof: ComparisonProofWithPublicParams,
+        width: BitWidth,
+    ) -> Result<(), ProofError> {
+        Self::try_verify_with_width_with_context(proof, width, &[])
+    }
+
+    pub fn verify_with_width_with_context(
+        proof: ComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_with_width_with_context(proof, width, context).is_ok()
+    }
+
+    pub fn try_verify_with_width_with_context(
+        proof: ComparisonProofWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        Self::try_verify_with_bits(proof, width.bits(), context)
+    }
+
+    pub(crate) fn try_verify_with_bits(
+        proof: ComparisonProofWithPublicParams,
+        bits: usize,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        if proof.proof.bi_point_vec.len() != bits {
+            return Err(ProofError::MalformedProof);
+        }
+        Self::try_verify_with_context(proof, context)
+    }
+
+    pub fn verify_ge_with_width(proof: ComparisonGeWithPublicParams, width: BitWidth) -> bool {
+        Self::try_verify_ge_with_width(proof, width).is_ok()
+    }
+
+    pub fn try_verify_ge_with_width(
+        proof: ComparisonGeWithPublicParams,
+        width: BitWidth,
+    ) -> Result<(), ProofError> {
+        Self::try_verify_ge_with_width_with_context(proof, width, &[])
+    }
+
+    pub fn verify_ge_with_width_with_context(
+        proof: ComparisonGeWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_ge_with_width_with_context(proof, width, context).is_ok()
+    }
+
+    pub fn try_verify_ge_with_width_with_context(
+        proof: ComparisonGeWithPublicParams,
+        width: BitWidth,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let ComparisonGeWithPublicParams {
+            proof,
+            a_point,
+            b_point,
+        } = proof;
+
+        Self::try_verify_with_width_with_context(
+            ComparisonProofWithPublicParams {
+                proof,
+                x: a_point - b_point,
+            },
+            width,
+            context,
+        )
+    }
+}
+
+pub fn decompose_number(input: i32) -> Vec<u8> {
+    decompose_u64(input.unsigned_abs() as u64)
+}
+
+/// the lowest `width` bits of input, least significant first
+pub fn decompose_with_width(input: u64, width: BitWidth) -> Vec<u8> {
+    decompose_bits(input, width.bits())
+}
+
+pub(crate) fn decompose_bits(input: u64, bits: usize) -> Vec<u8> {
+    (0..bits).map(|i| ((input >> i) & 1) as u8).collect()
+}
+
+fn decompose_u64(input: u64) -> Vec<u8> {
     let mut res = Vec::new();
     for i in format!("{:b}", input).into_bytes() {
         res.push(i - 48);
@@ -257,7 +903,6 @@ pub fn decompose_number(mut input: i32) -> Vec<u8> {
 }
 
 pub fn pow_scalar(a: Scalar, pow: usize) -> Scalar {
-    
     {
         let mut scalar = Scalar::one();
         for _ in 0..pow {
@@ -272,6 +917,7 @@ mod com_tests {
     use super::*;
     use rand::Rng;
     use std::assert_eq;
+    use wedpr_l_crypto_zkp_utils::get_random_scalar;
 
     #[test]
     fn decompose_number_test() {
@@ -295,7 +941,7 @@ mod com_tests {
             proof,
             x: x.comm.point,
         });
-        assert_eq!(res, true);
+        assert!(res);
     }
 
     #[test]
@@ -312,11 +958,341 @@ mod com_tests {
                 x: x.comm.point,
             });
 
-            let result_expect = if random > 0 { true } else { false };
+            let result_expect = random >= 0;
             assert_eq!(res, result_expect);
         }
     }
 
+    /// a proof built for one commitment must not verify against another
+    #[test]
+    fn com_protocol_wrong_commitment_test() {
+        let x = Comparison::commit(209348i32);
+        let y = Comparison::commit(1024i32);
+
+        let proof = Comparison::prove(&x);
+
+        let res = Comparison::verify(ComparisonProofWithPublicParams {
+            proof,
+            x: y.comm.point,
+        });
+        assert!(!res);
+    }
+
+    /// bit commitments of a non-negative number hashed together with an
+    /// unrelated (negative) x must be rejected
+    #[test]
+    fn com_protocol_unlinked_bits_test() {
+        let honest = Comparison::commit(209348i32);
+        let negative = Comparison::commit(-209348i32);
+
+        let forged = Comparison {
+            comm: ComparisonCommitment {
+                point: negative.comm.point,
+            },
+            secret: honest.secret,
+        };
+        let proof = Comparison::prove(&forged);
+
+        let res = Comparison::try_verify(ComparisonProofWithPublicParams {
+            proof,
+            x: negative.comm.point,
+        });
+        assert_eq!(res, Err(ProofError::BitDecompositionMismatch));
+
+        let proof = Comparison::prove(&negative);
+        let res = Comparison::try_verify(ComparisonProofWithPublicParams {
+            proof,
+            x: negative.comm.point,
+        });
+        assert_eq!(res, Err(ProofError::FirstEquationFailed));
+    }
+
+    /// prove a >= b
+    #[test]
+    fn ge_protocol_test() {
+        let test_data = vec![(10u64, 3u64), (5, 5), (0, 0), (1000000, 1), (u64::MAX, 0)];
+        for (a, b) in test_data {
+            let a = CommittedValue::commit(a);
+            let b = CommittedValue::commit(b);
+            let (a_point, b_point) = (a.comm.point, b.comm.point);
+
+            let proof = Comparison::prove_ge(&a, &b);
+
+            let res = Comparison::verify_ge(ComparisonGeWithPublicParams {
+                proof: proof.clone(),
+                a_point,
+                b_point,
+            });
+            assert!(res);
+
+            let res = Comparison::verify_ge(ComparisonGeWithPublicParams {
+                proof,
+                a_point: b_point,
+                b_point: a_point,
+            });
+            assert!(!res);
+        }
+    }
+
+    #[test]
+    fn random_ge_protocol_test() {
+        for _ in 0..50 {
+            let a = rand::thread_rng().gen_range(0u64..=1000000);
+            let b = rand::thread_rng().gen_range(0u64..=1000000);
+            let a_comm_secret = CommittedValue::commit(a);
+            let b_comm_secret = CommittedValue::commit(b);
+
+            let proof = Comparison::prove_ge(&a_comm_secret, &b_comm_secret);
+
+            let res = Comparison::verify_ge(ComparisonGeWithPublicParams {
+                proof,
+                a_point: a_comm_secret.comm.point,
+                b_point: b_comm_secret.comm.point,
+            });
+            assert_eq!(res, a >= b);
+        }
+    }
+
+    #[test]
+    fn decompose_with_width_test() {
+        assert_eq!(
+            vec![
+                0, 0, 1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0
+            ],
+            decompose_with_width(209348, BitWidth::Bits32)
+        );
+        assert_eq!(
+            vec![1; 64],
+            decompose_with_width(u64::MAX, BitWidth::Bits64)
+        );
+        assert_eq!(vec![0; 8], decompose_with_width(256, BitWidth::Bits8));
+    }
+
+    /// prove x >= 0 with a fixed number of bits
+    #[test]
+    fn width_com_protocol_test() {
+        let test_data = vec![
+            (0i64, BitWidth::Bits8, true),
+            (1, BitWidth::Bits8, true),
+            (255, BitWidth::Bits8, true),
+            (256, BitWidth::Bits8, false),
+            (-1, BitWidth::Bits8, false),
+            (65535, BitWidth::Bits16, true),
+            (65536, BitWidth::Bits16, false),
+            (209348, BitWidth::Bits32, true),
+            (-209348, BitWidth::Bits32, false),
+            (i64::MAX, BitWidth::Bits64, true),
+            (i64::MIN, BitWidth::Bits64, false),
+        ];
+        for (value, width, expect) in test_data {
+            let x = Comparison::commit_with_width(value, width);
+
+            let proof = Comparison::prove(&x);
+            assert_eq!(proof.bi_point_vec.len(), width.bits());
+
+            let res = Comparison::verify_with_width(
+                ComparisonProofWithPublicParams {
+                    proof,
+                    x: x.comm.point,
+                },
+                width,
+            );
+            assert_eq!(res, expect);
+        }
+    }
+
+    #[test]
+    fn width_mismatch_com_protocol_test() {
+        let x = Comparison::commit_with_width(100, BitWidth::Bits16);
+
+        let proof = Comparison::prove(&x);
+
+        let params = ComparisonProofWithPublicParams {
+            proof,
+            x: x.comm.point,
+        };
+        assert!(Comparison::verify(params.clone()));
+        assert!(Comparison::verify_with_width(
+            params.clone(),
+            BitWidth::Bits16
+        ));
+        assert!(!Comparison::verify_with_width(
+            params.clone(),
+            BitWidth::Bits8
+        ));
+        assert!(!Comparison::verify_with_width(params, BitWidth::Bits32));
+
+        // the variable-length proof of 100 only carries 7 bits
+        let x = Comparison::commit(100);
+        let proof = Comparison::prove(&x);
+        assert!(!Comparison::verify_with_width(
+            ComparisonProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+            },
+            BitWidth::Bits8,
+        ));
+    }
+
+    /// prove a >= b with a fixed number of bits
+    #[test]
+    fn width_ge_protocol_test() {
+        let test_data = vec![
+            (10u64, 3u64, BitWidth::Bits8, true),
+            (300, 3, BitWidth::Bits8, false),
+            (3, 10, BitWidth::Bits8, false),
+            (u64::MAX, 0, BitWidth::Bits64, true),
+        ];
+        for (a, b, width, expect) in test_data {
+            let a = CommittedValue::commit(a);
+            let b = CommittedValue::commit(b);
+
+            let proof = Comparison::prove_ge_with_width(&a, &b, width);
+            assert_eq!(proof.bi_point_vec.len(), width.bits());
+
+            let res = Comparison::verify_ge_with_width(
+                ComparisonGeWithPublicParams {
+                    proof,
+                    a_point: a.comm.point,
+                    b_point: b.comm.point,
+                },
+                width,
+            );
+            assert_eq!(res, expect);
+        }
+    }
+
+    /// prove x >= 0 with the inner product argument backend
+    #[test]
+    fn compact_com_protocol_test() {
+        let test_data = vec![
+            (0i64, BitWidth::Bits8, true),
+            (255, BitWidth::Bits8, true),
+            (256, BitWidth::Bits8, false),
+            (-1, BitWidth::Bits8, false),
+            (209348, BitWidth::Bits32, true),
+            (-209348, BitWidth::Bits32, false),
+            (i64::MAX, BitWidth::Bits64, true),
+            (i64::MIN, BitWidth::Bits64, false),
+        ];
+        for (value, width, expect) in test_data {
+            let x = Comparison::commit_with_width(value, width);
+
+            let proof = Comparison::prove_compact(&x, width);
+
+            let res = Comparison::verify_compact(
+                CompactComparisonProofWithPublicParams {
+                    proof,
+                    x: x.comm.point,
+                },
+                width,
+            );
+            assert_eq!(res, expect);
+        }
+
+        // the variable-length commitment works as long as it fits the width
+        let x = Comparison::commit(209348);
+        let proof = Comparison::prove_compact(&x, BitWidth::Bits32);
+        assert!(Comparison::verify_compact(
+            CompactComparisonProofWithPublicParams {
+                proof: proof.clone(),
+                x: x.comm.point,
+            },
+            BitWidth::Bits32,
+        ));
+        assert!(!Comparison::verify_compact(
+            CompactComparisonProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+            },
+            BitWidth::Bits64,
+        ));
+    }
+
+    /// prove x_j >= 0 for many commitments in one proof
+    #[test]
+    fn aggregated_com_protocol_test() {
+        for m in [1usize, 2, 3, 8, 13].iter() {
+            let values: Vec<i64> = (0..*m)
+                .map(|_| rand::thread_rng().gen_range(0i64..=u32::MAX as i64))
+                .collect();
+            let comms: Vec<Comparison> = values
+                .iter()
+                .map(|v| Comparison::commit_with_width(*v, BitWidth::Bits32))
+                .collect();
+
+            let proof = Comparison::prove_aggregated(&comms, BitWidth::Bits32);
+
+            let x_vec: Vec<RistrettoPoint> = comms.iter().map(|c| c.comm.point).collect();
+            let res = Comparison::verify_aggregated(
+                AggregatedComparisonProofWithPublicParams {
+                    proof: proof.clone(),
+                    x_vec: x_vec.clone(),
+                },
+                BitWidth::Bits32,
+            );
+            assert!(res);
+
+            // reordered, truncated or wrongly sized statements fail
+            let mut reversed = x_vec.clone();
+            reversed.reverse();
+            if *m > 1 {
+                assert!(!Comparison::verify_aggregated(
+                    AggregatedComparisonProofWithPublicParams {
+                        proof: proof.clone(),
+                        x_vec: reversed,
+                    },
+                    BitWidth::Bits32,
+                ));
+                assert!(!Comparison::verify_aggregated(
+                    AggregatedComparisonProofWithPublicParams {
+                        proof: proof.clone(),
+                        x_vec: x_vec[1..].to_vec(),
+                    },
+                    BitWidth::Bits32,
+                ));
+            }
+            assert!(!Comparison::verify_aggregated(
+                AggregatedComparisonProofWithPublicParams { proof, x_vec },
+                BitWidth::Bits16,
+            ));
+        }
+    }
+
+    #[test]
+    fn aggregated_negative_com_protocol_test() {
+        let comms = [
+            Comparison::commit_with_width(5, BitWidth::Bits16),
+            Comparison::commit_with_width(-5, BitWidth::Bits16),
+            Comparison::commit_with_width(70000, BitWidth::Bits16),
+            Comparison::commit_with_width(0, BitWidth::Bits16),
+        ];
+        for bad in 1..=2 {
+            let subset = vec![comms[0].clone(), comms[bad].clone(), comms[3].clone()];
+
+            let proof = Comparison::prove_aggregated(&subset, BitWidth::Bits16);
+
+            let res = Comparison::verify_aggregated(
+                AggregatedComparisonProofWithPublicParams {
+                    proof,
+                    x_vec: subset.iter().map(|c| c.comm.point).collect(),
+                },
+                BitWidth::Bits16,
+            );
+            assert!(!res);
+        }
+
+        let proof = Comparison::prove_aggregated(&[], BitWidth::Bits16);
+        assert!(!Comparison::verify_aggregated(
+            AggregatedComparisonProofWithPublicParams {
+                proof,
+                x_vec: vec![],
+            },
+            BitWidth::Bits16,
+        ));
+    }
+
     #[test]
     fn simple_comparison_protocol_test() {
         let a = 209348i32;
@@ -344,110 +1320,505 @@ mod com_tests {
             (b_vec_tmp, r_vec_tmp)
         };
 
-        let x = {
-            let mut tmp = Scalar::zero();
-            if a < 0 {
-                tmp = -Scalar::from(-a as u64);
-            } else {
-                tmp = Scalar::from(a as u64);
-            }
-            tmp
-        };
+        let x = Scalar::from(a as u64);
         let s = get_random_scalar();
         let x_commitment_point =
             RistrettoPoint::multiscalar_mul(&[x, s], &[*BASEPOINT_G1, *BASEPOINT_G2]);
 
-        if len > 1 {
-            let mut b_sigma = Scalar::from(2 as u64) * b_vec[1];
-            for i in 2..=len - 1 {
-                b_sigma += Scalar::from(2u64.pow(i as u32) as u64) * b_vec[i];
+        let mut b_sigma = Scalar::from(2_u64) * b_vec[1];
+        for (i, b_i) in b_vec.iter().enumerate().skip(2) {
+            b_sigma += Scalar::from(2u64.pow(i as u32)) * b_i;
+        }
+        b_vec[0] = x_commitment_point - b_sigma;
+
+        let mut r_sigma = Scalar::from(2_u64) * r_vec[1];
+        for (i, r_i) in r_vec.iter().enumerate().skip(2) {
+            r_sigma += Scalar::from(2u64.pow(i as u32)) * r_i;
+        }
+        r_vec[0] = s - r_sigma;
+
+        // P_0i = B_i and P_1i = B_i - G1, the bit's branch opens to r_i · G2
+        let branches: Vec<[RistrettoPoint; 2]> = b_vec
+            .iter()
+            .map(|b_i| [*b_i, b_i - *BASEPOINT_G1])
+            .collect();
+
+        let mut announcements = Vec::new();
+        let mut simulated = Vec::new();
+        for i in 0..len {
+            let bit = decomposed[i] as usize;
+            let k = get_random_scalar();
+            let c_other = get_random_scalar();
+            let s_other = get_random_scalar();
+            let mut a_i = [RistrettoPoint::default(); 2];
+            a_i[bit] = k * *BASEPOINT_G2;
+            a_i[1 - bit] = s_other * *BASEPOINT_G2 - c_other * branches[i][1 - bit];
+            announcements.push(a_i);
+            simulated.push((k, c_other, s_other));
+        }
+
+        let e = get_random_scalar();
+
+        let mut challenges = Vec::new();
+        let mut responses = Vec::new();
+        for i in 0..len {
+            let bit = decomposed[i] as usize;
+            let (k, c_other, s_other) = simulated[i];
+            let mut c_i = [Scalar::zero(); 2];
+            let mut s_i = [Scalar::zero(); 2];
+            c_i[bit] = e - c_other;
+            s_i[bit] = k + c_i[bit] * r_vec[i];
+            c_i[1 - bit] = c_other;
+            s_i[1 - bit] = s_other;
+            challenges.push(c_i);
+            responses.push(s_i);
+        }
+
+        /////////////verify 1 and 2/////////
+        for i in 0..len {
+            assert_eq!(challenges[i][0] + challenges[i][1], e);
+            for branch in 0..2 {
+                assert_eq!(
+                    responses[i][branch] * *BASEPOINT_G2,
+                    announcements[i][branch] + challenges[i][branch] * branches[i][branch]
+                );
             }
-            let b0 = x_commitment_point - b_sigma;
-            b_vec[0] = b0;
+        }
 
-            let mut r_sigma = Scalar::from(2 as u64) * r_vec[1];
-            for i in 2..=len - 1 {
-                r_sigma += Scalar::from(2u64.pow(i as u32) as u64) * r_vec[i];
+        /////////////verify 3/////////
+        let x_cal = RistrettoPoint::multiscalar_mul(
+            (0..len).map(|i| pow_scalar(Scalar::from(2u64), i)),
+            &b_vec,
+        );
+        assert_eq!(x_cal, x_commitment_point);
+    }
+
+    /// The bits recompose to x = -1, but B_0 opens to -1, neither 0 nor 1. A
+    /// prover without an opening of either branch can simulate both, yet not
+    /// with challenges that add up to e.
+    #[test]
+    fn forged_negative_com_protocol_test() {
+        let x = Comparison::commit_with_width(-1, BitWidth::Bits64);
+        let s = get_random_scalar();
+        let x_point =
+            RistrettoPoint::multiscalar_mul(&[-Scalar::one(), s], &[*BASEPOINT_G1, *BASEPOINT_G2]);
+
+        // B_i = r_i · G2 for i >= 1, B_0 takes the rest of x
+        let r_vec: Vec<Scalar> = (0..64).map(|_| get_random_scalar()).collect();
+        let mut b_vec: Vec<RistrettoPoint> = r_vec.iter().map(|r_i| r_i * *BASEPOINT_G2).collect();
+        let mut b_sigma = RistrettoPoint::default();
+        for (i, b_i) in b_vec.iter().enumerate().skip(1) {
+            b_sigma += pow_scalar(Scalar::from(2u64), i) * b_i;
+        }
+        b_vec[0] = x_point - b_sigma;
+
+        let mut transcript = Transcript::new(Protocol::Comparison, &[]);
+        transcript.append_point(b"x", &x_point);
+        transcript.append_u64(b"n", 64);
+        for b_i in &b_vec {
+            transcript.append_point(b"B_i", b_i);
+        }
+
+        let mut a0_vec = Vec::new();
+        let mut a1_vec = Vec::new();
+        let mut nonces = Vec::new();
+        for (i, b_i) in b_vec.iter().enumerate() {
+            let (c0, s0, c1, s1) = (
+                get_random_scalar(),
+                get_random_scalar(),
+                get_random_scalar(),
+                get_random_scalar(),
+            );
+            if i == 0 {
+                // both branches simulated
+                a0_vec.push(s0 * *BASEPOINT_G2 - c0 * b_i);
+                a1_vec.push(s1 * *BASEPOINT_G2 - c1 * (b_i - *BASEPOINT_G1));
+            } else {
+                // the 0 branch is real, the 1 branch simulated
+                a0_vec.push(s0 * *BASEPOINT_G2);
+                a1_vec.push(s1 * *BASEPOINT_G2 - c1 * (b_i - *BASEPOINT_G1));
             }
-            let r0 = s - r_sigma;
-            r_vec[0] = r0;
-        } else {
-            b_vec[0] = x_commitment_point;
-            r_vec[0] = s;
+            transcript.append_point(b"A_0i", &a0_vec[i]);
+            transcript.append_point(b"A_1i", &a1_vec[i]);
+            nonces.push((c0, s0, c1, s1));
         }
+        let e = transcript.challenge_scalar(b"e");
 
-        let b_i_prime_1 = get_random_scalar();
-        let mut b_i_prime_vec = Vec::new();
-        b_i_prime_vec.push(b_i_prime_1);
-        let mut b_sigma_prime = b_i_prime_1;
-        let mut bi_b_sigma_prime = b_i_prime_1 * Scalar::from(decomposed[0]);
-        for i in 1..=len - 1 {
-            let b_i_prime_i = get_random_scalar();
-            b_i_prime_vec.push(b_i_prime_i);
-            b_sigma_prime += b_i_prime_i;
-            bi_b_sigma_prime += b_i_prime_i * Scalar::from(decomposed[i]);
+        let mut c0_vec = Vec::new();
+        let mut s0_vec = Vec::new();
+        let mut s1_vec = Vec::new();
+        for (i, (c0, s0, c1, s1)) in nonces.into_iter().enumerate() {
+            if i == 0 {
+                c0_vec.push(c0);
+                s0_vec.push(s0);
+            } else {
+                c0_vec.push(e - c1);
+                s0_vec.push(s0 + (e - c1) * r_vec[i]);
+            }
+            s1_vec.push(s1);
         }
 
-        let r_prime = get_random_scalar();
-        let d1 = RistrettoPoint::multiscalar_mul(
-            &[b_sigma_prime, r_prime],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        let params = ComparisonProofWithPublicParams {
+            proof: ComparisonProof {
+                bi_point_vec: b_vec.iter().map(|b_i| b_i.compress()).collect(),
+                a0_point_vec: a0_vec.iter().map(|a0| a0.compress()).collect(),
+                a1_point_vec: a1_vec.iter().map(|a1| a1.compress()).collect(),
+                c0_vec,
+                s0_vec,
+                s1_vec,
+            },
+            x: x_point,
+        };
+        // the encoding round trips, so the forgery is as good as any proof off
+        // the wire
+        let bytes = params.proof.to_bytes();
+        assert_eq!(
+            ComparisonProof::from_bytes(&bytes),
+            Ok(params.proof.clone())
         );
 
-        let u_prime = get_random_scalar();
-        let d2 = RistrettoPoint::multiscalar_mul(
-            &[bi_b_sigma_prime, u_prime],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        assert_eq!(
+            Comparison::try_verify(params.clone()),
+            Err(ProofError::SecondEquationFailed)
+        );
+        assert_eq!(
+            Comparison::try_verify_with_width(params, BitWidth::Bits64),
+            Err(ProofError::SecondEquationFailed)
         );
 
-        let e = get_random_scalar();
+        // the honest prover fails the same way on -1
+        let res = Comparison::try_verify_with_width(
+            ComparisonProofWithPublicParams {
+                proof: Comparison::prove(&x),
+                x: x.comm.point,
+            },
+            BitWidth::Bits64,
+        );
+        assert!(res.is_err());
+    }
 
-        let (uj, rj, bj) = {
-            let mut uj_tmp = u_prime;
-            let mut rj_tmp = r_prime;
-            let mut bj_tmp = Vec::new();
-            for i in 0..=len - 1 {
-                let bj_i = Scalar::from(decomposed[i]) * pow_scalar(e, i) + b_i_prime_vec[i];
-                bj_tmp.push(bj_i);
-                uj_tmp += (pow_scalar(e, i) - (bj_i)) * r_vec[i];
-                rj_tmp += r_vec[i] * pow_scalar(e, i);
-            }
-            (uj_tmp, rj_tmp, bj_tmp)
+    fn malformed_verify(mutate: impl Fn(&mut ComparisonProof)) -> Result<(), ProofError> {
+        let x = Comparison::commit(209348);
+        let mut proof = Comparison::prove(&x);
+        mutate(&mut proof);
+
+        Comparison::try_verify(ComparisonProofWithPublicParams {
+            proof,
+            x: x.comm.point,
+        })
+    }
+
+    #[test]
+    fn empty_bit_commitments_test() {
+        let res = malformed_verify(|proof| {
+            proof.bi_point_vec.clear();
+            proof.a0_point_vec.clear();
+            proof.a1_point_vec.clear();
+            proof.c0_vec.clear();
+            proof.s0_vec.clear();
+            proof.s1_vec.clear();
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn short_response_vec_test() {
+        let res = malformed_verify(|proof| {
+            proof.s1_vec.pop();
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+
+        let res = malformed_verify(|proof| proof.c0_vec.clear());
+        assert_eq!(res, Err(ProofError::MalformedProof));
+
+        let res = malformed_verify(|proof| {
+            proof.a1_point_vec.pop();
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn long_response_vec_test() {
+        let res = malformed_verify(|proof| proof.s0_vec.push(Scalar::one()));
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn too_many_bit_commitments_test() {
+        let res = malformed_verify(|proof| {
+            let point = BASEPOINT_G2.compress();
+            proof.bi_point_vec.resize(MAX_BITS + 1, point);
+            proof.a0_point_vec.resize(MAX_BITS + 1, point);
+            proof.a1_point_vec.resize(MAX_BITS + 1, point);
+            proof.c0_vec.resize(MAX_BITS + 1, Scalar::zero());
+            proof.s0_vec.resize(MAX_BITS + 1, Scalar::zero());
+            proof.s1_vec.resize(MAX_BITS + 1, Scalar::zero());
+        });
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn identity_points_test() {
+        let res =
+            malformed_verify(|proof| proof.a0_point_vec[0] = RistrettoPoint::default().compress());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+
+        let res =
+            malformed_verify(|proof| proof.a1_point_vec[5] = RistrettoPoint::default().compress());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+
+        let res =
+            malformed_verify(|proof| proof.bi_point_vec[3] = RistrettoPoint::default().compress());
+        assert_eq!(res, Err(ProofError::DegeneratePoint));
+    }
+
+    /// a bit that is neither 0 nor 1 fails one of the two branches
+    #[test]
+    fn tampered_branch_test() {
+        let res = malformed_verify(|proof| proof.c0_vec[4] += Scalar::one());
+        assert!(res.is_err());
+
+        let res = malformed_verify(|proof| proof.s1_vec[4] += Scalar::one());
+        assert!(res.is_err());
+
+        // B_0 opens to 2 once recomposed from 64 one-bits to 2^64
+        let bits = vec![1u8; 64];
+        let x = Comparison::commit_bits(
+            pow_scalar(Scalar::from(2u64), 64),
+            get_random_scalar(),
+            bits,
+            &mut OsRng,
+        );
+        let res = Comparison::try_verify(ComparisonProofWithPublicParams {
+            proof: Comparison::prove(&x),
+            x: x.comm.point,
+        });
+        assert_eq!(res, Err(ProofError::SecondEquationFailed));
+    }
+
+    #[test]
+    fn malformed_width_test() {
+        let x = Comparison::commit_with_width(100, BitWidth::Bits8);
+        let mut proof = Comparison::prove(&x);
+        proof.s0_vec.pop();
+
+        let res = Comparison::try_verify_with_width(
+            ComparisonProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+            },
+            BitWidth::Bits8,
+        );
+        assert_eq!(res, Err(ProofError::MalformedProof));
+    }
+
+    #[test]
+    fn context_com_protocol_test() {
+        let x = Comparison::commit(209348);
+        let params = ComparisonProofWithPublicParams {
+            proof: Comparison::prove_with_context(&x, b"tx 1"),
+            x: x.comm.point,
         };
+        assert!(Comparison::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Comparison::verify_with_context(params.clone(), b"tx 2"));
+        assert!(!Comparison::verify(params));
 
-        /////////////verify 1/////////
+        let a = CommittedValue::commit(50);
+        let b = CommittedValue::commit(20);
+        let params = ComparisonGeWithPublicParams {
+            proof: Comparison::prove_ge_with_context(&a, &b, b"tx 1"),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+        };
+        assert!(Comparison::verify_ge_with_context(params.clone(), b"tx 1"));
+        assert!(!Comparison::verify_ge(params));
 
-        let left_1 = {
-            let mut tmp = d1;
-            for i in 0..=len - 1 {
-                tmp += b_vec[i] * pow_scalar(e, i);
-            }
-            tmp
+        let x = Comparison::commit_with_width(209348, BitWidth::Bits32);
+        let params = CompactComparisonProofWithPublicParams {
+            proof: Comparison::prove_compact_with_context(&x, BitWidth::Bits32, b"tx 1"),
+            x: x.comm.point,
         };
+        assert!(Comparison::verify_compact_with_context(
+            params.clone(),
+            BitWidth::Bits32,
+            b"tx 1"
+        ));
+        assert!(!Comparison::verify_compact(params, BitWidth::Bits32));
 
-        let bj_res = {
-            let mut tmp = Scalar::zero();
-            for i in 0..=len - 1 {
-                tmp += bj[i];
-            }
-            tmp
+        let comms: Vec<Comparison> = (0..3)
+            .map(|i| Comparison::commit_with_width(i * 1000, BitWidth::Bits16))
+            .collect();
+        let params = AggregatedComparisonProofWithPublicParams {
+            proof: Comparison::prove_aggregated_with_context(&comms, BitWidth::Bits16, b"tx 1"),
+            x_vec: comms.iter().map(|comm| comm.comm.point).collect(),
         };
+        assert!(Comparison::verify_aggregated_with_context(
+            params.clone(),
+            BitWidth::Bits16,
+            b"tx 1"
+        ));
+        assert!(!Comparison::verify_aggregated_with_context(
+            params,
+            BitWidth::Bits16,
+            b"tx 2"
+        ));
+    }
 
-        let right_1 =
-            RistrettoPoint::multiscalar_mul(&[bj_res, rj], &[*BASEPOINT_G1, *BASEPOINT_G2]);
-        assert_eq!(left_1, right_1);
+    #[test]
+    fn context_width_protocol_test() {
+        let x = Comparison::commit_with_width(209348, BitWidth::Bits32);
+        let params = ComparisonProofWithPublicParams {
+            proof: Comparison::prove_with_context(&x, b"tx 1"),
+            x: x.comm.point,
+        };
+        assert!(Comparison::verify_with_width_with_context(
+            params.clone(),
+            BitWidth::Bits32,
+            b"tx 1"
+        ));
+        assert!(!Comparison::verify_with_width_with_context(
+            params.clone(),
+            BitWidth::Bits32,
+            b"tx 2"
+        ));
+        assert!(!Comparison::verify_with_width(params, BitWidth::Bits32));
 
-        /////////////verify 2/////////
-        let left_2 = {
-            let mut tmp = d2;
-            for i in 0..=len - 1 {
-                tmp += b_vec[i] * (pow_scalar(e, i) - bj[i]);
-            }
-            tmp
+        let a = CommittedValue::commit(50);
+        let b = CommittedValue::commit(20);
+        let params = ComparisonGeWithPublicParams {
+            proof: Comparison::prove_ge_with_width_with_context(&a, &b, BitWidth::Bits8, b"tx 1"),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
         };
+        assert!(Comparison::verify_ge_with_width_with_context(
+            params.clone(),
+            BitWidth::Bits8,
+            b"tx 1"
+        ));
+        assert!(!Comparison::verify_ge_with_width_with_context(
+            params.clone(),
+            BitWidth::Bits8,
+            b"tx 2"
+        ));
+        assert!(!Comparison::verify_ge_with_width(params, BitWidth::Bits8));
+
+        // 3 >= 10 fails under any context
+        let a = CommittedValue::commit(3);
+        let b = CommittedValue::commit(10);
+        let params = ComparisonGeWithPublicParams {
+            proof: Comparison::prove_ge_with_width_with_context(&a, &b, BitWidth::Bits8, b"tx 1"),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+        };
+        assert!(!Comparison::verify_ge_with_width_with_context(
+            params,
+            BitWidth::Bits8,
+            b"tx 1"
+        ));
+    }
+
+    /// prove x >= 0 about a commitment made elsewhere, knowing its opening
+    #[test]
+    fn commit_with_blinding_com_protocol_test() {
+        let test_data = vec![(209348i64, true), (0, true), (-5, false)];
+        for (value, expect) in test_data {
+            let s = get_random_scalar();
+            let x_value = if value < 0 {
+                -Scalar::from(value.unsigned_abs())
+            } else {
+                Scalar::from(value as u64)
+            };
+            let external_point =
+                RistrettoPoint::multiscalar_mul(&[x_value, s], &[*BASEPOINT_G1, *BASEPOINT_G2]);
+
+            let x = Comparison::commit_with_blinding(value as i32, s);
+            assert_eq!(x.comm.point, external_point);
+            let res = Comparison::verify(ComparisonProofWithPublicParams {
+                proof: Comparison::prove(&x),
+                x: external_point,
+            });
+            assert_eq!(res, expect);
+
+            let x = Comparison::commit_with_width_and_blinding(value, BitWidth::Bits32, s);
+            assert_eq!(x.comm.point, external_point);
+            let res = Comparison::verify_with_width(
+                ComparisonProofWithPublicParams {
+                    proof: Comparison::prove(&x),
+                    x: external_point,
+                },
+                BitWidth::Bits32,
+            );
+            assert_eq!(res, expect);
+        }
+    }
+
+    #[test]
+    fn seeded_rng_com_protocol_test() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let x = Comparison::commit_with_width_and_rng(209348, BitWidth::Bits32, &mut rng);
+            let proof = Comparison::prove_with_rng(&x, &mut rng);
+            let compact = Comparison::prove_compact_with_rng(&x, BitWidth::Bits32, &mut rng);
+            (x, proof, compact)
+        };
+
+        let (x, proof, compact) = run(7);
+        assert_eq!(run(7), (x.clone(), proof.clone(), compact.clone()));
+        assert_ne!(run(8).1, proof);
+
+        assert!(Comparison::verify_with_width(
+            ComparisonProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+            },
+            BitWidth::Bits32,
+        ));
+        assert!(Comparison::verify_compact(
+            CompactComparisonProofWithPublicParams {
+                proof: compact,
+                x: x.comm.point,
+            },
+            BitWidth::Bits32,
+        ));
+    }
+
+    #[test]
+    fn bytes_com_protocol_test() {
+        let x = Comparison::commit(209348);
+        let proof = Comparison::prove(&x);
+
+        let bytes = proof.to_bytes();
+        // 18 bits: B_i, A_0i and A_1i, then c_0i, s_0i and s_1i, each prefixed
+        // with the count
+        assert_eq!(bytes.len(), 6 * (4 + 18 * 32));
+        let decoded = ComparisonProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(Comparison::verify(ComparisonProofWithPublicParams {
+            proof: decoded,
+            x: x.comm.point,
+        }));
+
+        // a bit count larger than the input can hold
+        let mut oversized = bytes.clone();
+        oversized[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            ComparisonProof::from_bytes(&oversized),
+            Err(ProofError::InvalidEncoding)
+        );
+        assert_eq!(
+            ComparisonProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn invalid_point_com_protocol_test() {
+        let res = malformed_verify(|proof| proof.bi_point_vec[3] = CompressedRistretto([0xff; 32]));
+        assert_eq!(res, Err(ProofError::InvalidEncoding));
 
-        let right_2 =
-            RistrettoPoint::multiscalar_mul(&[Scalar::zero(), uj], &[*BASEPOINT_G1, *BASEPOINT_G2]);
-        assert_eq!(left_2, right_2);
+        let res = malformed_verify(|proof| proof.a0_point_vec[2] = CompressedRistretto([0xff; 32]));
+        assert_eq!(res, Err(ProofError::InvalidEncoding));
     }
 }
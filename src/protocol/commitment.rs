@@ -0,0 +1,386 @@
+use crate::rng::random_scalar;
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
+
+/// Pedersen commitment [v] = v·G1 + r·G2, the same for every relation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Commitment {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub point: RistrettoPoint,
+}
+
+/// what a commitment opens to
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Opening {
+    pub value: u64,
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::scalar"))]
+    pub secret: Scalar,
+}
+
+/// a commitment together with its opening, as the prover holds it; one
+/// committed value can be passed to the `prove` of any relation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct CommittedValue {
+    pub comm: Commitment,
+    pub secret: Opening,
+}
+
+impl Opening {
+    pub fn new(value: u64, secret: Scalar) -> Self {
+        Self { value, secret }
+    }
+}
+
+impl CommittedValue {
+    pub fn commit(value: u64) -> Self {
+        Self::commit_with_rng(value, &mut OsRng)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> Self {
+        Self::commit_with_blinding(value, random_scalar(rng))
+    }
+
+    /// commit under a blinding chosen by the caller, e.g. to prove relations
+    /// about a commitment created by another system
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> Self {
+        Self::from_opening(Opening::new(value, secret))
+    }
+
+    pub fn from_opening(opening: Opening) -> Self {
+        let commitment_point = RistrettoPoint::multiscalar_mul(
+            &[Scalar::from(opening.value), opening.secret],
+            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        );
+
+        Self {
+            comm: Commitment {
+                point: commitment_point,
+            },
+            secret: opening,
+        }
+    }
+}
+
+/// signed counterpart of `Opening`: a negative value v is committed as
+/// −|v| in the scalar field, the way `Comparison::commit` maps x
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct SignedOpening {
+    pub value: i128,
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::scalar"))]
+    pub secret: Scalar,
+}
+
+/// a signed commitment together with its opening, accepted by the
+/// `prove_signed` of addition, subtraction, multiplication and division
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct SignedCommittedValue {
+    pub comm: Commitment,
+    pub secret: SignedOpening,
+}
+
+impl SignedOpening {
+    pub fn new(value: i128, secret: Scalar) -> Self {
+        Self { value, secret }
+    }
+}
+
+impl SignedCommittedValue {
+    pub fn commit(value: i128) -> Self {
+        Self::commit_with_rng(value, &mut OsRng)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: i128, rng: &mut R) -> Self {
+        Self::commit_with_blinding(value, random_scalar(rng))
+    }
+
+    pub fn commit_with_blinding(value: i128, secret: Scalar) -> Self {
+        Self::from_opening(SignedOpening::new(value, secret))
+    }
+
+    pub fn from_opening(opening: SignedOpening) -> Self {
+        let commitment_point = RistrettoPoint::multiscalar_mul(
+            &[scalar_from_i128(opening.value), opening.secret],
+            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        );
+
+        Self {
+            comm: Commitment {
+                point: commitment_point,
+            },
+            secret: opening,
+        }
+    }
+}
+
+/// an unsigned commitment is the signed commitment of the same value, so both
+/// kinds mix in one signed proof
+impl From<&CommittedValue> for SignedCommittedValue {
+    fn from(x: &CommittedValue) -> Self {
+        Self {
+            comm: x.comm.clone(),
+            secret: SignedOpening::new(x.secret.value as i128, x.secret.secret),
+        }
+    }
+}
+
+pub(crate) fn scalar_from_i64(value: i64) -> Scalar {
+    scalar_from_i128(value as i128)
+}
+
+pub(crate) fn scalar_from_i128(value: i128) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.unsigned_abs().to_le_bytes());
+    let magnitude = Scalar::from_bytes_mod_order(bytes);
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+#[cfg(test)]
+mod commitment_tests {
+    use super::*;
+    use crate::protocol::{
+        add::{Addition, AdditionProofWithPublicParams},
+        com::{Comparison, ComparisonGeWithPublicParams, ComparisonProofWithPublicParams},
+        div::{Division, DivisionWithPublicParams},
+        eq::Equality,
+        mul::{Multiplication, MultiplicationWithPublicParams},
+        range::{Range, RangeProofWithPublicParams},
+        sub::{Substraction, SubstractionWithPublicParams},
+    };
+    use wedpr_l_crypto_zkp_utils::get_random_scalar;
+
+    /// one committed value takes part in proofs of different relations
+    #[test]
+    fn shared_commitment_test() {
+        let a = CommittedValue::commit(6);
+        let b = Addition::commit(7);
+
+        let c = Addition::commit_c_witness(13, a.secret.secret, b.secret.secret);
+        let proof = Addition::prove(&a, &b, &c);
+        assert!(Addition::verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let c = Multiplication::commit(42);
+        let witness = Multiplication::witness(a.secret.secret, c.secret.secret, 7);
+        let proof = Multiplication::prove(&a, &b, &c, &witness);
+        assert!(Multiplication::verify(MultiplicationWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let proof = Comparison::prove(&Comparison::from_opening(&a));
+        assert!(Comparison::verify(ComparisonProofWithPublicParams {
+            proof,
+            x: a.comm.point,
+        }));
+
+        let proof = Comparison::prove_ge(&b, &a);
+        assert!(Comparison::verify_ge(ComparisonGeWithPublicParams {
+            proof,
+            a_point: b.comm.point,
+            b_point: a.comm.point,
+        }));
+
+        let a_again = Equality::commit(6);
+        let proof = Equality::prove_ab(&a, &a_again);
+        assert!(Equality::verify_ab(a.comm.point, a_again.comm.point, proof));
+    }
+
+    #[test]
+    fn commit_with_blinding_test() {
+        let secret = get_random_scalar();
+        let external_point = RistrettoPoint::multiscalar_mul(
+            &[Scalar::from(42u64), secret],
+            &[*BASEPOINT_G1, *BASEPOINT_G2],
+        );
+
+        let expected = CommittedValue::from_opening(Opening::new(42, secret));
+        assert_eq!(expected.comm.point, external_point);
+        assert_eq!(expected, CommittedValue::commit_with_blinding(42, secret));
+        assert_eq!(expected, Addition::commit_with_blinding(42, secret));
+        assert_eq!(expected, Substraction::commit_with_blinding(42, secret));
+        assert_eq!(expected, Multiplication::commit_with_blinding(42, secret));
+        assert_eq!(expected, Division::commit_with_blinding(42, secret));
+        assert_eq!(expected, Equality::commit_with_blinding(42, secret));
+        assert_eq!(
+            Range::commit_with_blinding(42, secret),
+            SignedCommittedValue::from(&expected)
+        );
+    }
+
+    /// the same seed gives the same commitments and proofs
+    #[test]
+    fn seeded_rng_test() {
+        use crate::protocol::eq::EqualityWithPublicParams;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let a = Addition::commit_with_rng(6, &mut rng);
+            let b = Substraction::commit_with_rng(6, &mut rng);
+            let c = Division::commit_with_rng(1, &mut rng);
+
+            let mul_witness = Multiplication::witness(a.secret.secret, a.secret.secret, 1);
+            let mul_proof = Multiplication::prove_with_rng(&a, &c, &a, &mul_witness, &mut rng);
+            let eq_proof = Equality::prove_with_rng(
+                &a,
+                &b,
+                &Equality::commit_c_witness(a.secret.secret, b.secret.secret),
+                &mut rng,
+            );
+            (a, b, c, mul_proof, eq_proof)
+        };
+
+        let (a, b, c, mul_proof, eq_proof) = run(7);
+        assert_eq!(
+            run(7),
+            (
+                a.clone(),
+                b.clone(),
+                c.clone(),
+                mul_proof.clone(),
+                eq_proof.clone()
+            )
+        );
+        assert_ne!(run(8).0, a);
+
+        assert!(Multiplication::verify(MultiplicationWithPublicParams {
+            proof: mul_proof,
+            a_point: a.comm.point,
+            b_point: c.comm.point,
+            c_point: a.comm.point,
+        }));
+        assert!(Equality::verify(EqualityWithPublicParams {
+            proof: eq_proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: a.comm.point - b.comm.point,
+        }));
+    }
+
+    #[test]
+    fn seeded_signed_commit_test() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            vec![
+                Addition::commit_i64_with_rng(-6, &mut rng),
+                Substraction::commit_i128_with_rng(-6, &mut rng),
+                Multiplication::commit_i64_with_rng(6, &mut rng),
+                Division::commit_i128_with_rng(6, &mut rng),
+            ]
+        };
+
+        let commitments = run(7);
+        assert_eq!(run(7), commitments);
+        assert_ne!(run(8), commitments);
+        assert_eq!(commitments[0].secret.value, -6);
+        assert_eq!(commitments[3].secret.value, 6);
+    }
+
+    /// negative values are −|v| in every relation, and unsigned commitments
+    /// mix with signed ones
+    #[test]
+    fn signed_relations_test() {
+        let secret = get_random_scalar();
+        assert_eq!(
+            SignedCommittedValue::commit_with_blinding(-5, secret)
+                .comm
+                .point,
+            -CommittedValue::commit_with_blinding(5, -secret).comm.point
+        );
+
+        let a = Addition::commit_i64(-15);
+        let b = SignedCommittedValue::from(&CommittedValue::commit(7));
+
+        let c = Addition::commit_c_witness_i64(-8, a.secret.secret, b.secret.secret);
+        let proof = Addition::prove_signed(&a, &b, &c);
+        assert!(Addition::verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let c = Substraction::commit_c_witness_i64(-22, a.secret.secret, b.secret.secret);
+        let proof = Substraction::prove_signed(&a, &b, &c);
+        assert!(Substraction::verify(SubstractionWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let c = Multiplication::commit_i64(-105);
+        let witness = Multiplication::witness_i64(a.secret.secret, c.secret.secret, 7);
+        let proof = Multiplication::prove_signed(&a, &b, &c, &witness);
+        assert!(Multiplication::verify(MultiplicationWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        // -105 / -7 = 15
+        let b = Division::commit_i64(-7);
+        let c = Division::commit_i64(15);
+        let dividend = Division::commit_i64(-105);
+        let witness = Division::witness_i64(dividend.secret.secret, c.secret.secret, -7);
+        let proof = Division::prove_signed(&dividend, &b, &c, &witness);
+        assert!(Division::verify(DivisionWithPublicParams {
+            proof,
+            a_point: dividend.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        // i128 values beyond the u64 domain
+        let a = Addition::commit_i128(-(1 << 100));
+        let b = Addition::commit_i128(1 << 100);
+        let c = Addition::commit_c_witness_i128(0, a.secret.secret, b.secret.secret);
+        let proof = Addition::prove_signed(&a, &b, &c);
+        assert!(Addition::verify(AdditionProofWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let x = Addition::commit_i64(-15);
+        let proof = Range::prove(&x, -20, 0);
+        assert!(Range::verify(RangeProofWithPublicParams {
+            proof,
+            x: x.comm.point,
+            lo: -20,
+            hi: 0,
+        }));
+    }
+}
@@ -1,65 +1,117 @@
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
-use wedpr_l_crypto_zkp_utils::{
-    get_random_scalar, hash_to_scalar, point_to_bytes, BASEPOINT_G1, BASEPOINT_G2,
+use crate::codec::{decompress, Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::commitment::{
+    scalar_from_i128, Commitment, CommittedValue, SignedCommittedValue, SignedOpening,
 };
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, MultiscalarMul},
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
 
-/// a / b = c
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Division {
-    pub comm: DivisionCommitment,
-    pub secret: DivisionSecret,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DivisionCommitment {
-    pub point: RistrettoPoint,
-}
-
+/// a / b = c with b != 0
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DivisionSecret {
-    pub value: u64,
-    pub secret: Scalar,
-}
+pub struct Division;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DivisionProof {
-    pub d1: RistrettoPoint,
-    pub d2: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d1: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d2: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d3: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub b_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub s_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub beta_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub inv_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
+    pub gamma_circumflex: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct DivisionWitness {
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::scalar"))]
     pub s: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DivisionWithPublicParams {
     pub proof: DivisionProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub c_point: RistrettoPoint,
 }
 
+impl DivisionProof {
+    /// canonical encoding, 256 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer
+            .compressed(&self.d1)
+            .compressed(&self.d2)
+            .compressed(&self.d3)
+            .scalar(&self.b_circumflex)
+            .scalar(&self.s_circumflex)
+            .scalar(&self.beta_circumflex)
+            .scalar(&self.inv_circumflex)
+            .scalar(&self.gamma_circumflex);
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, ProofError> {
+        Ok(Self {
+            d1: reader.compressed()?,
+            d2: reader.compressed()?,
+            d3: reader.compressed()?,
+            b_circumflex: reader.scalar()?,
+            s_circumflex: reader.scalar()?,
+            beta_circumflex: reader.scalar()?,
+            inv_circumflex: reader.scalar()?,
+            gamma_circumflex: reader.scalar()?,
+        })
+    }
+}
+
 impl Division {
-    pub fn commit(value: u64) -> Self {
-        let secret = get_random_scalar();
-        let commitment_point = RistrettoPoint::multiscalar_mul(
-            &[Scalar::from(value), secret],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
 
-        Self {
-            comm: DivisionCommitment {
-                point: commitment_point,
-            },
-            secret: DivisionSecret {
-                value,
-                secret,
-            },
-        }
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
     }
 
     pub fn witness(a_secret: Scalar, c_secret: Scalar, val_b: u64) -> DivisionWitness {
@@ -68,32 +120,148 @@ impl Division {
         DivisionWitness { s }
     }
 
+    /// commit to a signed value, see `SignedCommittedValue`
+    pub fn commit_i64(value: i64) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value as i128)
+    }
+
+    pub fn commit_i128(value: i128) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value)
+    }
+
+    pub fn commit_i64_with_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value as i128, rng)
+    }
+
+    pub fn commit_i128_with_rng<R: RngCore + CryptoRng>(
+        value: i128,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn witness_i64(a_secret: Scalar, c_secret: Scalar, val_b: i64) -> DivisionWitness {
+        Self::witness_i128(a_secret, c_secret, val_b as i128)
+    }
+
+    pub fn witness_i128(a_secret: Scalar, c_secret: Scalar, val_b: i128) -> DivisionWitness {
+        let s = a_secret - c_secret * scalar_from_i128(val_b);
+
+        DivisionWitness { s }
+    }
+
     pub fn prove(
-        a: &Division,
-        b: &Division,
-        c: &Division,
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &DivisionWitness,
+    ) -> DivisionProof {
+        Self::prove_with_context(a, b, c, witness, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &DivisionWitness,
+        context: &[u8],
+    ) -> DivisionProof {
+        Self::prove_with_context_and_rng(a, b, c, witness, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &DivisionWitness,
+        rng: &mut R,
+    ) -> DivisionProof {
+        Self::prove_with_context_and_rng(a, b, c, witness, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &DivisionWitness,
+        context: &[u8],
+        rng: &mut R,
+    ) -> DivisionProof {
+        Self::prove_signed_with_context_and_rng(
+            &SignedCommittedValue::from(a),
+            &SignedCommittedValue::from(b),
+            &SignedCommittedValue::from(c),
+            witness,
+            context,
+            rng,
+        )
+    }
+
+    /// prove for signed commitments, every relation holding in the scalar
+    /// field where a negative v is −|v|
+    pub fn prove_signed(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &DivisionWitness,
+    ) -> DivisionProof {
+        Self::prove_signed_with_context(a, b, c, witness, &[])
+    }
+
+    pub fn prove_signed_with_context(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &DivisionWitness,
+        context: &[u8],
+    ) -> DivisionProof {
+        Self::prove_signed_with_context_and_rng(a, b, c, witness, context, &mut OsRng)
+    }
+
+    pub fn prove_signed_with_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &DivisionWitness,
+        rng: &mut R,
+    ) -> DivisionProof {
+        Self::prove_signed_with_context_and_rng(a, b, c, witness, &[], rng)
+    }
+
+    pub fn prove_signed_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
         witness: &DivisionWitness,
+        context: &[u8],
+        rng: &mut R,
     ) -> DivisionProof {
-        let Division {
-            comm: DivisionCommitment { point: a_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: a_point },
             secret:
-                DivisionSecret {
+                SignedOpening {
                     value: _a_value,
                     secret: _a_secret,
                 },
         } = a;
-        let Division {
-            comm: DivisionCommitment { point: b_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: b_point },
             secret:
-                DivisionSecret {
+                SignedOpening {
                     value: b_value,
                     secret: b_secret,
                 },
         } = b;
-        let Division {
-            comm: DivisionCommitment { point: c_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: c_point },
             secret:
-                DivisionSecret {
+                SignedOpening {
                     value: _c_value,
                     secret: _c_secret,
                 },
@@ -101,63 +269,106 @@ impl Division {
 
         let DivisionWitness { s } = witness;
 
-        let b_2 = get_random_scalar();
-        let s_2 = get_random_scalar();
-        let beta_2 = get_random_scalar();
+        let mut transcript = Transcript::new(Protocol::Division, context);
+        transcript.append_point(b"a", a_point);
+        transcript.append_point(b"b", b_point);
+        transcript.append_point(b"c", c_point);
+
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_scalar(b"b_value", &scalar_from_i128(*b_value))
+            .rekey_with_witness_scalar(b"b_secret", b_secret)
+            .rekey_with_witness_scalar(b"s", s)
+            .finalize(rng);
+        let b_2 = random_scalar(&mut rng);
+        let s_2 = random_scalar(&mut rng);
+        let beta_2 = random_scalar(&mut rng);
+        let inv_2 = random_scalar(&mut rng);
+        let gamma_2 = random_scalar(&mut rng);
+
+        // b != 0: g = [b]^inv · h^gamma with inv = 1/b and gamma = -β/b, an
+        // opening no prover can find for a commitment to zero
+        let inv = scalar_from_i128(*b_value).invert();
+        let gamma = -(b_secret * inv);
 
         let d1 = RistrettoPoint::multiscalar_mul(&[b_2, s_2], &[*c_point, *BASEPOINT_G2]);
         let d2 = RistrettoPoint::multiscalar_mul(&[b_2, beta_2], &[*BASEPOINT_G1, *BASEPOINT_G2]);
+        let d3 = RistrettoPoint::multiscalar_mul(&[inv_2, gamma_2], &[*b_point, *BASEPOINT_G2]);
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(a_point));
-        hash_vec.append(&mut point_to_bytes(b_point));
-        hash_vec.append(&mut point_to_bytes(c_point));
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
+        transcript.append_point(b"d1", &d1);
+        transcript.append_point(b"d2", &d2);
+        transcript.append_point(b"d3", &d3);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         //b^= b0 + e · b, sˆ = s0 + e · s, βˆ = β0 + e · β;
-        let b_circumflex = b_2 + e * Scalar::from(*b_value);
+        let b_circumflex = b_2 + e * scalar_from_i128(*b_value);
         let s_circumflex = s_2 + e * s;
         let beta_circumflex = beta_2 + e * b_secret;
+        let inv_circumflex = inv_2 + e * inv;
+        let gamma_circumflex = gamma_2 + e * gamma;
 
         DivisionProof {
-            d1,
-            d2,
+            d1: d1.compress(),
+            d2: d2.compress(),
+            d3: d3.compress(),
             b_circumflex,
             s_circumflex,
             beta_circumflex,
+            inv_circumflex,
+            gamma_circumflex,
         }
     }
 
     pub fn verify(proof: DivisionWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: DivisionWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: DivisionWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: DivisionWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
         let DivisionWithPublicParams {
             proof:
                 DivisionProof {
                     d1,
                     d2,
+                    d3,
                     b_circumflex,
                     s_circumflex,
                     beta_circumflex,
+                    inv_circumflex,
+                    gamma_circumflex,
                 },
             a_point,
             b_point,
             c_point,
         } = proof;
+        let d1 = decompress(&d1)?;
+        let d2 = decompress(&d2)?;
+        let d3 = decompress(&d3)?;
+
+        if d1.is_identity() || d2.is_identity() || d3.is_identity() {
+            return Err(ProofError::DegeneratePoint);
+        }
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(&a_point));
-        hash_vec.append(&mut point_to_bytes(&b_point));
-        hash_vec.append(&mut point_to_bytes(&c_point));
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
+        let mut transcript = Transcript::new(Protocol::Division, context);
+        transcript.append_point(b"a", &a_point);
+        transcript.append_point(b"b", &b_point);
+        transcript.append_point(b"c", &c_point);
+        transcript.append_point(b"d1", &d1);
+        transcript.append_point(b"d2", &d2);
+        transcript.append_point(b"d3", &d3);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         // d1 · [a]e = [c]ˆb · hs
         let left = d1 + e * a_point;
@@ -171,8 +382,23 @@ impl Division {
             &[b_circumflex, beta_circumflex],
             &[*BASEPOINT_G1, *BASEPOINT_G2],
         );
+        // d3 · ge = [b]ˆinv · hˆγ
+        let left3 = d3 + e * *BASEPOINT_G1;
+        let right3 = RistrettoPoint::multiscalar_mul(
+            &[inv_circumflex, gamma_circumflex],
+            &[b_point, *BASEPOINT_G2],
+        );
 
-        left2 == right2 && left == right
+        if left != right {
+            return Err(ProofError::FirstEquationFailed);
+        }
+        if left2 != right2 {
+            return Err(ProofError::SecondEquationFailed);
+        }
+        if left3 != right3 {
+            return Err(ProofError::ZeroDivisor);
+        }
+        Ok(())
     }
 }
 
@@ -203,6 +429,149 @@ mod division_tests {
             c_point: c_comm_secret.comm.point,
         });
 
-        assert_eq!(true, result);
+        assert!(result);
+    }
+
+    #[test]
+    fn verify_error_test() {
+        let (a, b, c) = (10, 5, 2);
+
+        let a_comm_secret = Division::commit(a);
+        let b_comm_secret = Division::commit(b);
+        let c_comm_secret = Division::commit(c);
+
+        let witness =
+            Division::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Division::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        let mut tampered = proof.clone();
+        tampered.s_circumflex += Scalar::one();
+        let result = Division::try_verify(DivisionWithPublicParams {
+            proof: tampered,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::FirstEquationFailed));
+
+        let mut tampered = proof;
+        tampered.beta_circumflex += Scalar::one();
+        let result = Division::try_verify(DivisionWithPublicParams {
+            proof: tampered,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::SecondEquationFailed));
+    }
+
+    #[test]
+    fn zero_divisor_test() {
+        // 0 = 0 · c holds for any c, so the quotient is only bound when b != 0
+        let a_comm_secret = Division::commit(0);
+        let b_comm_secret = Division::commit(0);
+        let c_comm_secret = Division::commit(12345);
+
+        let witness =
+            Division::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, 0);
+
+        let proof = Division::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        let result = Division::try_verify(DivisionWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::ZeroDivisor));
+    }
+
+    #[test]
+    fn identity_point_test() {
+        let (a, b, c) = (10, 5, 2);
+
+        let a_comm_secret = Division::commit(a);
+        let b_comm_secret = Division::commit(b);
+        let c_comm_secret = Division::commit(c);
+
+        let witness =
+            Division::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Division::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        for i in 0..3 {
+            let mut tampered = proof.clone();
+            match i {
+                0 => tampered.d1 = RistrettoPoint::default().compress(),
+                1 => tampered.d2 = RistrettoPoint::default().compress(),
+                _ => tampered.d3 = RistrettoPoint::default().compress(),
+            }
+            let result = Division::try_verify(DivisionWithPublicParams {
+                proof: tampered,
+                a_point: a_comm_secret.comm.point,
+                b_point: b_comm_secret.comm.point,
+                c_point: c_comm_secret.comm.point,
+            });
+            assert_eq!(result, Err(ProofError::DegeneratePoint));
+        }
+    }
+
+    #[test]
+    fn context_test() {
+        let (a, b, c) = (10, 5, 2);
+
+        let a_comm_secret = Division::commit(a);
+        let b_comm_secret = Division::commit(b);
+        let c_comm_secret = Division::commit(c);
+
+        let witness =
+            Division::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Division::prove_with_context(
+            &a_comm_secret,
+            &b_comm_secret,
+            &c_comm_secret,
+            &witness,
+            b"tx 1",
+        );
+        let params = DivisionWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        };
+
+        assert!(Division::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Division::verify_with_context(params.clone(), b"tx 2"));
+        assert_eq!(
+            Division::try_verify(params),
+            Err(ProofError::FirstEquationFailed)
+        );
+    }
+
+    #[test]
+    fn bytes_test() {
+        let (a, b, c) = (10, 5, 2);
+
+        let a_comm_secret = Division::commit(a);
+        let b_comm_secret = Division::commit(b);
+        let c_comm_secret = Division::commit(c);
+
+        let witness =
+            Division::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+        let proof = Division::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 256);
+        assert_eq!(DivisionProof::from_bytes(&bytes), Ok(proof));
+
+        // gamma_circumflex with its top byte set is not reduced mod l
+        let mut non_canonical = bytes;
+        non_canonical[255] = 0xff;
+        assert_eq!(
+            DivisionProof::from_bytes(&non_canonical),
+            Err(ProofError::InvalidEncoding)
+        );
     }
 }
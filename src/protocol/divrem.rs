@@ -0,0 +1,365 @@
+use crate::codec::{Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::bulletproof::{
+    prove_aggregated_range, verify_aggregated_range, AggregatedComparisonProofWithPublicParams,
+    CompactComparisonProof,
+};
+use crate::protocol::commitment::{Commitment, CommittedValue, Opening};
+use crate::protocol::div::{Division, DivisionProof, DivisionWithPublicParams};
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::BASEPOINT_G1;
+
+/// a = b·c + r with 0 <= r < b, i.e. c = a / b and r = a % b, truncating
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DivisionWithRemainder;
+
+/// a - r = b·c as an exact division, and c, r, b - r - 1 and b in [0, 2^64)
+/// in one aggregated range proof. The bounds keep b·c + r below 2^129, far
+/// from the group order, so a - r = b·c holds over the integers and not only
+/// modulo it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DivisionWithRemainderProof {
+    pub quotient: DivisionProof,
+    pub bounds: CompactComparisonProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DivisionWithRemainderWithPublicParams {
+    pub proof: DivisionWithRemainderProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub c_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub r_point: RistrettoPoint,
+}
+
+impl DivisionWithRemainderProof {
+    /// canonical encoding, the division proof followed by the range proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.quotient.write(&mut writer);
+        self.bounds.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            quotient: DivisionProof::read(&mut reader)?,
+            bounds: CompactComparisonProof::read(&mut reader)?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl DivisionWithRemainder {
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
+    }
+
+    pub fn prove(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        r: &CommittedValue,
+    ) -> DivisionWithRemainderProof {
+        Self::prove_with_context(a, b, c, r, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        r: &CommittedValue,
+        context: &[u8],
+    ) -> DivisionWithRemainderProof {
+        Self::prove_with_context_and_rng(a, b, c, r, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        r: &CommittedValue,
+        rng: &mut R,
+    ) -> DivisionWithRemainderProof {
+        Self::prove_with_context_and_rng(a, b, c, r, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        r: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> DivisionWithRemainderProof {
+        let sub_context = divrem_context(
+            &a.comm.point,
+            &b.comm.point,
+            &c.comm.point,
+            &r.comm.point,
+            context,
+        );
+
+        // [a - r] = [a] - [r] opens to b·c
+        let a_minus_r = CommittedValue {
+            comm: Commitment {
+                point: a.comm.point - r.comm.point,
+            },
+            secret: Opening::new(
+                a.secret.value.wrapping_sub(r.secret.value),
+                a.secret.secret - r.secret.secret,
+            ),
+        };
+        let witness = Division::witness(a_minus_r.secret.secret, c.secret.secret, b.secret.value);
+        let quotient =
+            Division::prove_with_context_and_rng(&a_minus_r, b, c, &witness, &sub_context, rng);
+
+        // [b - r - 1] = [b] - [r] - G1; r >= b wraps it beyond what [b - r - 1]
+        // opens to and the proof fails
+        let values = [
+            c.secret.value,
+            r.secret.value,
+            b.secret.value.wrapping_sub(r.secret.value).wrapping_sub(1),
+            b.secret.value,
+        ];
+        let gammas = [
+            c.secret.secret,
+            r.secret.secret,
+            b.secret.secret - r.secret.secret,
+            b.secret.secret,
+        ];
+        let bounds = prove_aggregated_range(&values, &gammas, 64, &sub_context, rng);
+
+        DivisionWithRemainderProof { quotient, bounds }
+    }
+
+    pub fn verify(proof: DivisionWithRemainderWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: DivisionWithRemainderWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(
+        proof: DivisionWithRemainderWithPublicParams,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: DivisionWithRemainderWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let DivisionWithRemainderWithPublicParams {
+            proof: DivisionWithRemainderProof { quotient, bounds },
+            a_point,
+            b_point,
+            c_point,
+            r_point,
+        } = proof;
+
+        let sub_context = divrem_context(&a_point, &b_point, &c_point, &r_point, context);
+
+        Division::try_verify_with_context(
+            DivisionWithPublicParams {
+                proof: quotient,
+                a_point: a_point - r_point,
+                b_point,
+                c_point,
+            },
+            &sub_context,
+        )?;
+        verify_aggregated_range(
+            AggregatedComparisonProofWithPublicParams {
+                proof: bounds,
+                x_vec: vec![c_point, r_point, b_point - r_point - *BASEPOINT_G1, b_point],
+            },
+            64,
+            &sub_context,
+        )
+    }
+}
+
+/// binds both sub-proofs to [a], [b], [c] and [r] under the composite's label
+fn divrem_context(
+    a_point: &RistrettoPoint,
+    b_point: &RistrettoPoint,
+    c_point: &RistrettoPoint,
+    r_point: &RistrettoPoint,
+    context: &[u8],
+) -> [u8; 32] {
+    let mut transcript = Transcript::new(Protocol::DivisionWithRemainder, context);
+    transcript.append_point(b"a", a_point);
+    transcript.append_point(b"b", b_point);
+    transcript.append_point(b"c", c_point);
+    transcript.append_point(b"r", r_point);
+    transcript.sub_context()
+}
+
+#[cfg(test)]
+mod division_with_remainder_tests {
+    use super::*;
+
+    fn prove_and_verify(a: u64, b: u64, c: u64, r: u64) -> Result<(), ProofError> {
+        let a_comm_secret = DivisionWithRemainder::commit(a);
+        let b_comm_secret = DivisionWithRemainder::commit(b);
+        let c_comm_secret = DivisionWithRemainder::commit(c);
+        let r_comm_secret = DivisionWithRemainder::commit(r);
+
+        let proof = DivisionWithRemainder::prove(
+            &a_comm_secret,
+            &b_comm_secret,
+            &c_comm_secret,
+            &r_comm_secret,
+        );
+
+        DivisionWithRemainder::try_verify(DivisionWithRemainderWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+            r_point: r_comm_secret.comm.point,
+        })
+    }
+
+    #[test]
+    fn it_works() {
+        let test_data = vec![(17u64, 5u64), (20, 5), (4, 5), (0, 1), (1_000_000_007, 97)];
+        for (a, b) in test_data {
+            assert_eq!(
+                prove_and_verify(a, b, a / b, a % b),
+                Ok(()),
+                "{} / {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn verify_error_test() {
+        // a quotient one too small leaves a remainder of b + 2
+        assert!(prove_and_verify(17, 5, 2, 7).is_err());
+        // the right remainder with the wrong quotient
+        assert!(prove_and_verify(17, 5, 4, 2).is_err());
+        // r = b is not a remainder
+        assert!(prove_and_verify(20, 5, 3, 5).is_err());
+        // b = 0 would admit any quotient
+        assert!(prove_and_verify(7, 0, 3, 7).is_err());
+    }
+
+    /// 7 = 2·c holds in the scalar field for c = 7/2 mod ℓ, which is no
+    /// quotient at all: the division proof alone accepts it, the range proof
+    /// on c does not
+    #[test]
+    fn field_quotient_test() {
+        use crate::rng::random_scalar;
+        use curve25519_dalek::traits::MultiscalarMul;
+        use wedpr_l_crypto_zkp_utils::BASEPOINT_G2;
+
+        let a = DivisionWithRemainder::commit(7);
+        let b = DivisionWithRemainder::commit(2);
+        let r = DivisionWithRemainder::commit(0);
+        let c_value = Scalar::from(7u64) * Scalar::from(2u64).invert();
+        let c_secret = random_scalar(&mut OsRng);
+        let c = CommittedValue {
+            comm: Commitment {
+                point: RistrettoPoint::multiscalar_mul(
+                    &[c_value, c_secret],
+                    &[*BASEPOINT_G1, *BASEPOINT_G2],
+                ),
+            },
+            // the value only feeds the range proof, no u64 opens [c]
+            secret: Opening::new(0, c_secret),
+        };
+
+        let witness = Division::witness(a.secret.secret, c.secret.secret, 2);
+        assert!(Division::verify(DivisionWithPublicParams {
+            proof: Division::prove(&a, &b, &c, &witness),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+
+        let result = DivisionWithRemainder::try_verify(DivisionWithRemainderWithPublicParams {
+            proof: DivisionWithRemainder::prove(&a, &b, &c, &r),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+            r_point: r.comm.point,
+        });
+        assert!(result.is_err());
+    }
+
+    /// the division proof inside does not verify on its own
+    #[test]
+    fn lifted_quotient_test() {
+        let a = DivisionWithRemainder::commit(17);
+        let b = DivisionWithRemainder::commit(5);
+        let c = DivisionWithRemainder::commit(3);
+        let r = DivisionWithRemainder::commit(2);
+        let proof = DivisionWithRemainder::prove(&a, &b, &c, &r);
+
+        assert!(!Division::verify(DivisionWithPublicParams {
+            proof: proof.quotient,
+            a_point: a.comm.point - r.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        }));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let a = DivisionWithRemainder::commit(17);
+        let b = DivisionWithRemainder::commit(5);
+        let c = DivisionWithRemainder::commit(3);
+        let r = DivisionWithRemainder::commit(2);
+        let proof = DivisionWithRemainder::prove_with_context(&a, &b, &c, &r, b"tx 1");
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            DivisionWithRemainderProof::from_bytes(&bytes),
+            Ok(proof.clone())
+        );
+        assert_eq!(
+            DivisionWithRemainderProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+
+        let params = DivisionWithRemainderWithPublicParams {
+            proof,
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+            r_point: r.comm.point,
+        };
+        assert!(DivisionWithRemainder::verify_with_context(
+            params.clone(),
+            b"tx 1"
+        ));
+        assert!(!DivisionWithRemainder::verify(params));
+    }
+}
@@ -1,141 +1,289 @@
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
-use wedpr_l_crypto_zkp_utils::{
-    get_random_scalar, hash_to_scalar, point_to_bytes, BASEPOINT_G1, BASEPOINT_G2,
+use crate::codec::{decompress, Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::commitment::{Commitment, CommittedValue, Opening};
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::IsIdentity,
 };
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::BASEPOINT_G2;
 
-/// eq
+/// a = b, shown through [c] = [a] - [b] = (a_sec - b_sec)·G2 having no G1 part
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Equality {
-    pub comm: EqualityCommitment,
-    pub secret: EqualitySecret,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct EqualityCommitment {
-    pub point: RistrettoPoint,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct EqualitySecret {
-    pub value: u64,
-    pub secret: Scalar,
-}
+pub struct Equality;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EqualityProof {
-    pub d: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub u: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EqualityWithPublicParams {
     pub proof: EqualityProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub c_point: RistrettoPoint,
 }
 
+impl EqualityProof {
+    /// canonical encoding, 64 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.compressed(&self.d).scalar(&self.u);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            d: reader.compressed()?,
+            u: reader.scalar()?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
 impl Equality {
-    pub fn commit(value: u64) -> Self {
-        let secret = get_random_scalar();
-        let commitment_point = RistrettoPoint::multiscalar_mul(
-            &[Scalar::from(value), secret],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
 
-        Self {
-            comm: EqualityCommitment {
-                point: commitment_point,
-            },
-            secret: EqualitySecret {
-                value,
-                secret,
-            },
-        }
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
     }
 
-    pub fn commit_c_witness(a_sec: Scalar, b_sec: Scalar) -> Self {
+    pub fn commit_c_witness(a_sec: Scalar, b_sec: Scalar) -> CommittedValue {
         let t = a_sec - b_sec;
         let commitment_point = t * *BASEPOINT_G2;
 
-        Self {
-            comm: EqualityCommitment {
+        CommittedValue {
+            comm: Commitment {
                 point: commitment_point,
             },
-            secret: EqualitySecret {
+            secret: Opening {
                 value: 0,
                 secret: t,
             },
         }
     }
 
-    pub fn prove(a: &Equality, b: &Equality, c: &Equality) -> EqualityProof {
-        let Equality {
-            comm: EqualityCommitment { point: a_point },
+    /// prove a = b without the caller building [c]
+    pub fn prove_ab(a: &CommittedValue, b: &CommittedValue) -> EqualityProof {
+        Self::prove_ab_with_context(a, b, &[])
+    }
+
+    pub fn prove_ab_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        context: &[u8],
+    ) -> EqualityProof {
+        Self::prove_ab_with_context_and_rng(a, b, context, &mut OsRng)
+    }
+
+    pub fn prove_ab_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        rng: &mut R,
+    ) -> EqualityProof {
+        Self::prove_ab_with_context_and_rng(a, b, &[], rng)
+    }
+
+    pub fn prove_ab_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> EqualityProof {
+        let c = Self::commit_c_witness(a.secret.secret, b.secret.secret);
+
+        Self::prove_with_context_and_rng(a, b, &c, context, rng)
+    }
+
+    pub fn prove(a: &CommittedValue, b: &CommittedValue, c: &CommittedValue) -> EqualityProof {
+        Self::prove_with_context(a, b, c, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+    ) -> EqualityProof {
+        Self::prove_with_context_and_rng(a, b, c, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        rng: &mut R,
+    ) -> EqualityProof {
+        Self::prove_with_context_and_rng(a, b, c, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        context: &[u8],
+        rng: &mut R,
+    ) -> EqualityProof {
+        let CommittedValue {
+            comm: Commitment { point: a_point },
             secret:
-                EqualitySecret {
+                Opening {
                     value: _a_value,
                     secret: _a_secret,
                 },
         } = a;
-        let Equality {
-            comm: EqualityCommitment { point: b_point },
+        let CommittedValue {
+            comm: Commitment { point: b_point },
             secret:
-                EqualitySecret {
+                Opening {
                     value: _b_value,
                     secret: _b_secret,
                 },
         } = b;
-        let Equality {
-            comm: EqualityCommitment { point: _c_point },
+        let CommittedValue {
+            comm: Commitment { point: c_point },
             secret:
-                EqualitySecret {
+                Opening {
                     value: _c_value,
                     secret: c_secret,
                 },
         } = c;
 
-        let x = get_random_scalar();
+        let mut transcript = Transcript::new(Protocol::Equality, context);
+        transcript.append_point(b"a", a_point);
+        transcript.append_point(b"b", b_point);
+        transcript.append_point(b"c", c_point);
 
-        let d = x * *BASEPOINT_G2;
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_scalar(b"c_secret", c_secret)
+            .finalize(rng);
+        let x = random_scalar(&mut rng);
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(a_point));
-        hash_vec.append(&mut point_to_bytes(b_point));
-        hash_vec.append(&mut point_to_bytes(&d));
+        let d = x * *BASEPOINT_G2;
+        transcript.append_point(b"d", &d);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         //u = x + e · t;
         let u = x + e * c_secret;
 
-        EqualityProof { d, u }
+        EqualityProof { d: d.compress(), u }
+    }
+
+    /// verify a = b from [a] and [b] alone, [c] being [a] - [b]
+    pub fn verify_ab(
+        a_point: RistrettoPoint,
+        b_point: RistrettoPoint,
+        proof: EqualityProof,
+    ) -> bool {
+        Self::try_verify_ab(a_point, b_point, proof).is_ok()
+    }
+
+    pub fn try_verify_ab(
+        a_point: RistrettoPoint,
+        b_point: RistrettoPoint,
+        proof: EqualityProof,
+    ) -> Result<(), ProofError> {
+        Self::try_verify_ab_with_context(a_point, b_point, proof, &[])
+    }
+
+    pub fn verify_ab_with_context(
+        a_point: RistrettoPoint,
+        b_point: RistrettoPoint,
+        proof: EqualityProof,
+        context: &[u8],
+    ) -> bool {
+        Self::try_verify_ab_with_context(a_point, b_point, proof, context).is_ok()
+    }
+
+    pub fn try_verify_ab_with_context(
+        a_point: RistrettoPoint,
+        b_point: RistrettoPoint,
+        proof: EqualityProof,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        Self::try_verify_with_context(
+            EqualityWithPublicParams {
+                proof,
+                a_point,
+                b_point,
+                c_point: a_point - b_point,
+            },
+            context,
+        )
     }
 
     pub fn verify(proof: EqualityWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: EqualityWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: EqualityWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: EqualityWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
         let EqualityWithPublicParams {
             proof: EqualityProof { d, u },
             a_point,
             b_point,
-            c_point: _c_point,
+            c_point,
         } = proof;
+        let d = decompress(&d)?;
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(&a_point));
-        hash_vec.append(&mut point_to_bytes(&b_point));
-        hash_vec.append(&mut point_to_bytes(&d));
+        if d.is_identity() {
+            return Err(ProofError::DegeneratePoint);
+        }
+        if c_point != a_point - b_point {
+            return Err(ProofError::HomomorphicMismatch);
+        }
 
-        let e = hash_to_scalar(&hash_vec);
+        let mut transcript = Transcript::new(Protocol::Equality, context);
+        transcript.append_point(b"a", &a_point);
+        transcript.append_point(b"b", &b_point);
+        transcript.append_point(b"c", &c_point);
+        transcript.append_point(b"d", &d);
 
-        //d · ([a]/[b])e = hu;
-        let left = d + (a_point - b_point) * e;
+        let e = transcript.challenge_scalar(b"e");
+
+        //d · [c]^e = hu;
+        let left = d + c_point * e;
         let right = u * *BASEPOINT_G2;
 
-        left == right
+        if left != right {
+            return Err(ProofError::FirstEquationFailed);
+        }
+        Ok(())
     }
 }
 
@@ -164,7 +312,7 @@ mod equality_tests {
             c_point: c_comm_secret.comm.point,
         });
 
-        assert_eq!(true, result);
+        assert!(result);
     }
 
     #[test]
@@ -192,9 +340,171 @@ mod equality_tests {
                 c_point: c_comm_secret.comm.point,
             });
 
-            let result_expect = if a == b { true } else { false };
+            let result_expect = a == b;
 
             assert_eq!(result, result_expect);
         }
     }
+
+    #[test]
+    fn verify_error_test() {
+        let a_comm_secret = Equality::commit(30);
+        let b_comm_secret = Equality::commit(31);
+        let c_comm_secret =
+            Equality::commit_c_witness(a_comm_secret.secret.secret, b_comm_secret.secret.secret);
+
+        let proof = Equality::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+        let result = Equality::try_verify(EqualityWithPublicParams {
+            proof: proof.clone(),
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::HomomorphicMismatch));
+
+        let result =
+            Equality::try_verify_ab(a_comm_secret.comm.point, b_comm_secret.comm.point, proof);
+        assert_eq!(result, Err(ProofError::FirstEquationFailed));
+    }
+
+    #[test]
+    fn identity_point_test() {
+        let a_comm_secret = Equality::commit(30);
+        let b_comm_secret = Equality::commit(30);
+        let c_comm_secret =
+            Equality::commit_c_witness(a_comm_secret.secret.secret, b_comm_secret.secret.secret);
+
+        let mut proof = Equality::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+        proof.d = RistrettoPoint::default().compress();
+
+        let result = Equality::try_verify(EqualityWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::DegeneratePoint));
+    }
+
+    #[test]
+    fn context_test() {
+        let a_comm_secret = Equality::commit(30);
+        let b_comm_secret = Equality::commit(30);
+        let c_comm_secret =
+            Equality::commit_c_witness(a_comm_secret.secret.secret, b_comm_secret.secret.secret);
+
+        let proof =
+            Equality::prove_with_context(&a_comm_secret, &b_comm_secret, &c_comm_secret, b"tx 1");
+        let params = EqualityWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        };
+
+        assert!(Equality::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Equality::verify_with_context(params.clone(), b"tx 2"));
+        assert_eq!(
+            Equality::try_verify(params),
+            Err(ProofError::FirstEquationFailed)
+        );
+    }
+
+    #[test]
+    fn verify_ab_test() {
+        let test_data = vec![
+            (30u64, 30u64, true),
+            (30, 31, false),
+            (0, 0, true),
+            (u64::MAX, 0, false),
+        ];
+        for (a, b, expect) in test_data {
+            let a_comm_secret = Equality::commit(a);
+            let b_comm_secret = Equality::commit(b);
+
+            let proof = Equality::prove_ab(&a_comm_secret, &b_comm_secret);
+
+            let result =
+                Equality::verify_ab(a_comm_secret.comm.point, b_comm_secret.comm.point, proof);
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn seeded_rng_ab_test() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let a = Equality::commit(30);
+        let b = Equality::commit(30);
+        let run = |seed: u64| Equality::prove_ab_with_rng(&a, &b, &mut StdRng::seed_from_u64(seed));
+
+        let proof = run(7);
+        assert_eq!(run(7), proof);
+        assert_ne!(run(8), proof);
+        assert!(Equality::verify_ab(a.comm.point, b.comm.point, proof));
+    }
+
+    #[test]
+    fn context_ab_test() {
+        let a = Equality::commit(30);
+        let b = Equality::commit(30);
+        let proof = Equality::prove_ab_with_context(&a, &b, b"tx 1");
+
+        assert!(Equality::verify_ab_with_context(
+            a.comm.point,
+            b.comm.point,
+            proof.clone(),
+            b"tx 1"
+        ));
+        assert!(!Equality::verify_ab_with_context(
+            a.comm.point,
+            b.comm.point,
+            proof.clone(),
+            b"tx 2"
+        ));
+        assert!(!Equality::verify_ab(a.comm.point, b.comm.point, proof));
+    }
+
+    /// [c] is part of the statement: a proof for one [c] does not carry over
+    /// to another, even one the verifier would otherwise accept
+    #[test]
+    fn unbound_c_test() {
+        let a_comm_secret = Equality::commit(30);
+        let b_comm_secret = Equality::commit(30);
+        let c_comm_secret =
+            Equality::commit_c_witness(a_comm_secret.secret.secret, b_comm_secret.secret.secret);
+
+        let proof = Equality::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+        assert!(Equality::verify_ab(
+            a_comm_secret.comm.point,
+            b_comm_secret.comm.point,
+            proof.clone()
+        ));
+
+        let result = Equality::try_verify(EqualityWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: Equality::commit(0).comm.point,
+        });
+        assert_eq!(result, Err(ProofError::HomomorphicMismatch));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let a_comm_secret = Equality::commit(30);
+        let b_comm_secret = Equality::commit(30);
+        let c_comm_secret =
+            Equality::commit_c_witness(a_comm_secret.secret.secret, b_comm_secret.secret.secret);
+        let proof = Equality::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(EqualityProof::from_bytes(&bytes), Ok(proof));
+        assert_eq!(
+            EqualityProof::from_bytes(&bytes[..63]),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
 }
@@ -1,6 +1,13 @@
 pub mod add;
+pub mod any;
+pub mod bulletproof;
+pub mod checked_add;
+pub mod checked_sub;
 pub mod com;
+pub mod commitment;
 pub mod div;
+pub mod divrem;
 pub mod eq;
 pub mod mul;
+pub mod range;
 pub mod sub;
@@ -1,65 +1,97 @@
-use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
-use wedpr_l_crypto_zkp_utils::{
-    get_random_scalar, hash_to_scalar, point_to_bytes, BASEPOINT_G1, BASEPOINT_G2,
+use crate::codec::{decompress, Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::commitment::{
+    scalar_from_i128, Commitment, CommittedValue, SignedCommittedValue, SignedOpening,
 };
+use crate::rng::random_scalar;
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, MultiscalarMul},
+};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
 
 /// a * b = c
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Multiplication {
-    pub comm: MultiplicationCommitment,
-    pub secret: MultiplicationSecret,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MultiplicationCommitment {
-    pub point: RistrettoPoint,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MultiplicationSecret {
-    pub value: u64,
-    pub secret: Scalar,
-}
+pub struct Multiplication;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiplicationProof {
-    pub d1: RistrettoPoint,
-    pub d2: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d1: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::compressed"))]
+    pub d2: CompressedRistretto,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub b_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub s_circumflex: Scalar,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::scalar"))]
     pub beta_circumflex: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-secrets",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct MultiplicationWitness {
+    #[cfg_attr(feature = "serde-secrets", serde(with = "crate::serde_hex::scalar"))]
     pub s: Scalar,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiplicationWithPublicParams {
     pub proof: MultiplicationProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub a_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub b_point: RistrettoPoint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
     pub c_point: RistrettoPoint,
 }
 
+impl MultiplicationProof {
+    /// canonical encoding, 160 bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .compressed(&self.d1)
+            .compressed(&self.d2)
+            .scalar(&self.b_circumflex)
+            .scalar(&self.s_circumflex)
+            .scalar(&self.beta_circumflex);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            d1: reader.compressed()?,
+            d2: reader.compressed()?,
+            b_circumflex: reader.scalar()?,
+            s_circumflex: reader.scalar()?,
+            beta_circumflex: reader.scalar()?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
 impl Multiplication {
-    pub fn commit(value: u64) -> Self {
-        let secret = get_random_scalar();
-        let commitment_point = RistrettoPoint::multiscalar_mul(
-            &[Scalar::from(value), secret],
-            &[*BASEPOINT_G1, *BASEPOINT_G2],
-        );
+    pub fn commit(value: u64) -> CommittedValue {
+        CommittedValue::commit(value)
+    }
 
-        Self {
-            comm: MultiplicationCommitment {
-                point: commitment_point,
-            },
-            secret: MultiplicationSecret {
-                value,
-                secret,
-            },
-        }
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> CommittedValue {
+        CommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn commit_with_blinding(value: u64, secret: Scalar) -> CommittedValue {
+        CommittedValue::commit_with_blinding(value, secret)
     }
 
     pub fn witness(a_secret: Scalar, c_secret: Scalar, val_b: u64) -> MultiplicationWitness {
@@ -68,32 +100,148 @@ impl Multiplication {
         MultiplicationWitness { s }
     }
 
+    /// commit to a signed value, see `SignedCommittedValue`
+    pub fn commit_i64(value: i64) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value as i128)
+    }
+
+    pub fn commit_i128(value: i128) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value)
+    }
+
+    pub fn commit_i64_with_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value as i128, rng)
+    }
+
+    pub fn commit_i128_with_rng<R: RngCore + CryptoRng>(
+        value: i128,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value, rng)
+    }
+
+    pub fn witness_i64(a_secret: Scalar, c_secret: Scalar, val_b: i64) -> MultiplicationWitness {
+        Self::witness_i128(a_secret, c_secret, val_b as i128)
+    }
+
+    pub fn witness_i128(a_secret: Scalar, c_secret: Scalar, val_b: i128) -> MultiplicationWitness {
+        let s = c_secret - a_secret * scalar_from_i128(val_b);
+
+        MultiplicationWitness { s }
+    }
+
     pub fn prove(
-        a: &Multiplication,
-        b: &Multiplication,
-        c: &Multiplication,
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &MultiplicationWitness,
+    ) -> MultiplicationProof {
+        Self::prove_with_context(a, b, c, witness, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &MultiplicationWitness,
+        context: &[u8],
+    ) -> MultiplicationProof {
+        Self::prove_with_context_and_rng(a, b, c, witness, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &MultiplicationWitness,
+        rng: &mut R,
+    ) -> MultiplicationProof {
+        Self::prove_with_context_and_rng(a, b, c, witness, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &CommittedValue,
+        b: &CommittedValue,
+        c: &CommittedValue,
+        witness: &MultiplicationWitness,
+        context: &[u8],
+        rng: &mut R,
+    ) -> MultiplicationProof {
+        Self::prove_signed_with_context_and_rng(
+            &SignedCommittedValue::from(a),
+            &SignedCommittedValue::from(b),
+            &SignedCommittedValue::from(c),
+            witness,
+            context,
+            rng,
+        )
+    }
+
+    /// prove for signed commitments, every relation holding in the scalar
+    /// field where a negative v is −|v|
+    pub fn prove_signed(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &MultiplicationWitness,
+    ) -> MultiplicationProof {
+        Self::prove_signed_with_context(a, b, c, witness, &[])
+    }
+
+    pub fn prove_signed_with_context(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
         witness: &MultiplicationWitness,
+        context: &[u8],
     ) -> MultiplicationProof {
-        let Multiplication {
-            comm: MultiplicationCommitment { point: a_point },
+        Self::prove_signed_with_context_and_rng(a, b, c, witness, context, &mut OsRng)
+    }
+
+    pub fn prove_signed_with_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &MultiplicationWitness,
+        rng: &mut R,
+    ) -> MultiplicationProof {
+        Self::prove_signed_with_context_and_rng(a, b, c, witness, &[], rng)
+    }
+
+    pub fn prove_signed_with_context_and_rng<R: RngCore + CryptoRng>(
+        a: &SignedCommittedValue,
+        b: &SignedCommittedValue,
+        c: &SignedCommittedValue,
+        witness: &MultiplicationWitness,
+        context: &[u8],
+        rng: &mut R,
+    ) -> MultiplicationProof {
+        let SignedCommittedValue {
+            comm: Commitment { point: a_point },
             secret:
-                MultiplicationSecret {
+                SignedOpening {
                     value: _a_value,
                     secret: _a_secret,
                 },
         } = a;
-        let Multiplication {
-            comm: MultiplicationCommitment { point: b_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: b_point },
             secret:
-                MultiplicationSecret {
+                SignedOpening {
                     value: b_value,
                     secret: b_secret,
                 },
         } = b;
-        let Multiplication {
-            comm: MultiplicationCommitment { point: c_point },
+        let SignedCommittedValue {
+            comm: Commitment { point: c_point },
             secret:
-                MultiplicationSecret {
+                SignedOpening {
                     value: _c_value,
                     secret: _c_secret,
                 },
@@ -101,32 +249,37 @@ impl Multiplication {
 
         let MultiplicationWitness { s } = witness;
 
-        let b_2 = get_random_scalar();
-        let s_2 = get_random_scalar();
-        let beta_2 = get_random_scalar();
+        let mut transcript = Transcript::new(Protocol::Multiplication, context);
+        transcript.append_point(b"a", a_point);
+        transcript.append_point(b"b", b_point);
+        transcript.append_point(b"c", c_point);
+
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_scalar(b"b_value", &scalar_from_i128(*b_value))
+            .rekey_with_witness_scalar(b"b_secret", b_secret)
+            .rekey_with_witness_scalar(b"s", s)
+            .finalize(rng);
+        let b_2 = random_scalar(&mut rng);
+        let s_2 = random_scalar(&mut rng);
+        let beta_2 = random_scalar(&mut rng);
 
         let d1 = RistrettoPoint::multiscalar_mul(&[b_2, s_2], &[*a_point, *BASEPOINT_G2]);
         let d2 = RistrettoPoint::multiscalar_mul(&[b_2, beta_2], &[*BASEPOINT_G1, *BASEPOINT_G2]);
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(a_point));
-        hash_vec.append(&mut point_to_bytes(b_point));
-        hash_vec.append(&mut point_to_bytes(c_point));
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
+        transcript.append_point(b"d1", &d1);
+        transcript.append_point(b"d2", &d2);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         //b^= b0 + e · b, sˆ = s0 + e · s, βˆ = β0 + e · β;
-        let b_circumflex = b_2 + e * Scalar::from(*b_value);
+        let b_circumflex = b_2 + e * scalar_from_i128(*b_value);
         let s_circumflex = s_2 + e * s; // c_secret = s
         let beta_circumflex = beta_2 + e * b_secret;
 
         MultiplicationProof {
-            d1,
-            d2,
+            d1: d1.compress(),
+            d2: d2.compress(),
             b_circumflex,
             s_circumflex,
             beta_circumflex,
@@ -134,6 +287,21 @@ impl Multiplication {
     }
 
     pub fn verify(proof: MultiplicationWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: MultiplicationWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: MultiplicationWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: MultiplicationWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
         let MultiplicationWithPublicParams {
             proof:
                 MultiplicationProof {
@@ -147,17 +315,21 @@ impl Multiplication {
             b_point,
             c_point,
         } = proof;
+        let d1 = decompress(&d1)?;
+        let d2 = decompress(&d2)?;
+
+        if d1.is_identity() || d2.is_identity() {
+            return Err(ProofError::DegeneratePoint);
+        }
 
-        let mut hash_vec = Vec::new();
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G1));
-        hash_vec.append(&mut point_to_bytes(&BASEPOINT_G2));
-        hash_vec.append(&mut point_to_bytes(&a_point));
-        hash_vec.append(&mut point_to_bytes(&b_point));
-        hash_vec.append(&mut point_to_bytes(&c_point));
-        hash_vec.append(&mut point_to_bytes(&d1));
-        hash_vec.append(&mut point_to_bytes(&d2));
+        let mut transcript = Transcript::new(Protocol::Multiplication, context);
+        transcript.append_point(b"a", &a_point);
+        transcript.append_point(b"b", &b_point);
+        transcript.append_point(b"c", &c_point);
+        transcript.append_point(b"d1", &d1);
+        transcript.append_point(b"d2", &d2);
 
-        let e = hash_to_scalar(&hash_vec);
+        let e = transcript.challenge_scalar(b"e");
 
         // d1 · [c]e = [a]ˆb · hs
         let left = d1 + e * c_point;
@@ -172,7 +344,13 @@ impl Multiplication {
             &[*BASEPOINT_G1, *BASEPOINT_G2],
         );
 
-        left2 == right2 && left == right
+        if left != right {
+            return Err(ProofError::FirstEquationFailed);
+        }
+        if left2 != right2 {
+            return Err(ProofError::SecondEquationFailed);
+        }
+        Ok(())
     }
 }
 
@@ -203,6 +381,131 @@ mod multiplication_tests {
             c_point: c_comm_secret.comm.point,
         });
 
-        assert_eq!(true, result);
+        assert!(result);
+    }
+
+    #[test]
+    fn verify_error_test() {
+        let (a, b, c) = (30, 18, 540);
+
+        let a_comm_secret = Multiplication::commit(a);
+        let b_comm_secret = Multiplication::commit(b);
+        let c_comm_secret = Multiplication::commit(c);
+
+        let witness =
+            Multiplication::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Multiplication::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        let mut tampered = proof.clone();
+        tampered.s_circumflex += Scalar::one();
+        let result = Multiplication::try_verify(MultiplicationWithPublicParams {
+            proof: tampered,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::FirstEquationFailed));
+
+        let mut tampered = proof;
+        tampered.beta_circumflex += Scalar::one();
+        let result = Multiplication::try_verify(MultiplicationWithPublicParams {
+            proof: tampered,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        });
+        assert_eq!(result, Err(ProofError::SecondEquationFailed));
+    }
+
+    #[test]
+    fn identity_point_test() {
+        let (a, b, c) = (30, 18, 540);
+
+        let a_comm_secret = Multiplication::commit(a);
+        let b_comm_secret = Multiplication::commit(b);
+        let c_comm_secret = Multiplication::commit(c);
+
+        let witness =
+            Multiplication::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Multiplication::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        for i in 0..2 {
+            let mut tampered = proof.clone();
+            if i == 0 {
+                tampered.d1 = RistrettoPoint::default().compress();
+            } else {
+                tampered.d2 = RistrettoPoint::default().compress();
+            }
+            let result = Multiplication::try_verify(MultiplicationWithPublicParams {
+                proof: tampered,
+                a_point: a_comm_secret.comm.point,
+                b_point: b_comm_secret.comm.point,
+                c_point: c_comm_secret.comm.point,
+            });
+            assert_eq!(result, Err(ProofError::DegeneratePoint));
+        }
+    }
+
+    #[test]
+    fn context_test() {
+        let (a, b, c) = (30, 18, 540);
+
+        let a_comm_secret = Multiplication::commit(a);
+        let b_comm_secret = Multiplication::commit(b);
+        let c_comm_secret = Multiplication::commit(c);
+
+        let witness =
+            Multiplication::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+
+        let proof = Multiplication::prove_with_context(
+            &a_comm_secret,
+            &b_comm_secret,
+            &c_comm_secret,
+            &witness,
+            b"tx 1",
+        );
+        let params = MultiplicationWithPublicParams {
+            proof,
+            a_point: a_comm_secret.comm.point,
+            b_point: b_comm_secret.comm.point,
+            c_point: c_comm_secret.comm.point,
+        };
+
+        assert!(Multiplication::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Multiplication::verify_with_context(
+            params.clone(),
+            b"tx 2"
+        ));
+        assert_eq!(
+            Multiplication::try_verify(params),
+            Err(ProofError::FirstEquationFailed)
+        );
+    }
+
+    #[test]
+    fn bytes_test() {
+        let (a, b, c) = (30, 18, 540);
+
+        let a_comm_secret = Multiplication::commit(a);
+        let b_comm_secret = Multiplication::commit(b);
+        let c_comm_secret = Multiplication::commit(c);
+
+        let witness =
+            Multiplication::witness(a_comm_secret.secret.secret, c_comm_secret.secret.secret, b);
+        let proof = Multiplication::prove(&a_comm_secret, &b_comm_secret, &c_comm_secret, &witness);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 160);
+        assert_eq!(MultiplicationProof::from_bytes(&bytes), Ok(proof));
+
+        // beta_circumflex with its top byte set is not reduced mod l
+        let mut non_canonical = bytes;
+        non_canonical[159] = 0xff;
+        assert_eq!(
+            MultiplicationProof::from_bytes(&non_canonical),
+            Err(ProofError::InvalidEncoding)
+        );
     }
 }
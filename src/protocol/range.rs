@@ -0,0 +1,442 @@
+use crate::codec::{Reader, Writer};
+use crate::error::ProofError;
+use crate::protocol::bulletproof::{
+    prove_aggregated_range, verify_aggregated_range, AggregatedComparisonProofWithPublicParams,
+    CompactComparisonProof,
+};
+use crate::protocol::commitment::{
+    scalar_from_i64, Commitment, CommittedValue, SignedCommittedValue, SignedOpening,
+};
+use crate::transcript::{Protocol, Transcript};
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, OsRng, RngCore};
+use wedpr_l_crypto_zkp_utils::BASEPOINT_G1;
+
+/// lo <= x <= hi for a signed commitment, so x and the bounds may be negative
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range;
+
+/// x - lo and hi - x in [0, 2^n) in one aggregated range proof, n the
+/// smallest power of two that holds hi - lo. Both values adding up to
+/// hi - lo < 2^64 rules out either of them wrapping around the group order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeProof {
+    pub bounds: CompactComparisonProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeProofWithPublicParams {
+    pub proof: RangeProof,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex::point"))]
+    pub x: RistrettoPoint,
+    pub lo: i64,
+    pub hi: i64,
+}
+
+impl RangeProof {
+    /// canonical encoding, that of the aggregated range proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.bounds.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        let mut reader = Reader::new(bytes);
+        let proof = Self {
+            bounds: CompactComparisonProof::read(&mut reader)?,
+        };
+        reader.finish()?;
+        Ok(proof)
+    }
+}
+
+impl Range {
+    pub fn commit(value: i64) -> SignedCommittedValue {
+        SignedCommittedValue::commit(value as i128)
+    }
+
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(
+        value: i64,
+        rng: &mut R,
+    ) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_rng(value as i128, rng)
+    }
+
+    pub fn commit_with_blinding(value: i64, secret: Scalar) -> SignedCommittedValue {
+        SignedCommittedValue::commit_with_blinding(value as i128, secret)
+    }
+
+    /// prove lo <= x <= hi for an unsigned commitment made by any relation
+    pub fn from_opening(x: &CommittedValue) -> SignedCommittedValue {
+        x.into()
+    }
+
+    pub fn prove(x: &SignedCommittedValue, lo: i64, hi: i64) -> RangeProof {
+        Self::prove_with_context(x, lo, hi, &[])
+    }
+
+    /// prove bound to `context` (e.g. a transaction hash), the proof only
+    /// verifies under the same context
+    pub fn prove_with_context(
+        x: &SignedCommittedValue,
+        lo: i64,
+        hi: i64,
+        context: &[u8],
+    ) -> RangeProof {
+        Self::prove_with_context_and_rng(x, lo, hi, context, &mut OsRng)
+    }
+
+    /// prove with nonces drawn from `rng` instead of the OS RNG
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        x: &SignedCommittedValue,
+        lo: i64,
+        hi: i64,
+        rng: &mut R,
+    ) -> RangeProof {
+        Self::prove_with_context_and_rng(x, lo, hi, &[], rng)
+    }
+
+    pub fn prove_with_context_and_rng<R: RngCore + CryptoRng>(
+        x: &SignedCommittedValue,
+        lo: i64,
+        hi: i64,
+        context: &[u8],
+        rng: &mut R,
+    ) -> RangeProof {
+        let SignedCommittedValue {
+            comm: Commitment { point: x_point },
+            secret: SignedOpening { value, secret },
+        } = x;
+
+        // [x] - lo·G1 opens to (x - lo, s) and hi·G1 - [x] to (hi - x, -s); out
+        // of range either one is cut to n bits and the proof fails
+        let values = [(*value - lo as i128) as u64, (hi as i128 - *value) as u64];
+        let sub_context = range_context(x_point, lo, hi, context);
+
+        RangeProof {
+            bounds: prove_aggregated_range(
+                &values,
+                &[*secret, -secret],
+                range_bits(lo, hi),
+                &sub_context,
+                rng,
+            ),
+        }
+    }
+
+    pub fn verify(proof: RangeProofWithPublicParams) -> bool {
+        Self::try_verify(proof).is_ok()
+    }
+
+    pub fn try_verify(proof: RangeProofWithPublicParams) -> Result<(), ProofError> {
+        Self::try_verify_with_context(proof, &[])
+    }
+
+    pub fn verify_with_context(proof: RangeProofWithPublicParams, context: &[u8]) -> bool {
+        Self::try_verify_with_context(proof, context).is_ok()
+    }
+
+    pub fn try_verify_with_context(
+        proof: RangeProofWithPublicParams,
+        context: &[u8],
+    ) -> Result<(), ProofError> {
+        let RangeProofWithPublicParams {
+            proof: RangeProof { bounds },
+            x: x_point,
+            lo,
+            hi,
+        } = proof;
+
+        if lo > hi {
+            return Err(ProofError::MalformedProof);
+        }
+
+        let lower_point = x_point - scalar_from_i64(lo) * *BASEPOINT_G1;
+        let upper_point = scalar_from_i64(hi) * *BASEPOINT_G1 - x_point;
+        let sub_context = range_context(&x_point, lo, hi, context);
+
+        verify_aggregated_range(
+            AggregatedComparisonProofWithPublicParams {
+                proof: bounds,
+                x_vec: vec![lower_point, upper_point],
+            },
+            range_bits(lo, hi),
+            &sub_context,
+        )
+    }
+}
+
+/// bit length of hi - lo rounded up to a power of two, so that both x - lo
+/// and hi - x fit in it when lo <= x <= hi
+fn range_bits(lo: i64, hi: i64) -> usize {
+    let span = (hi as i128 - lo as i128).max(0) as u64;
+    (64 - span.leading_zeros() as usize).next_power_of_two()
+}
+
+/// binds the aggregated proof to [x], lo and hi themselves rather than only
+/// to [x] - lo·G1 and hi·G1 - [x]
+fn range_context(x_point: &RistrettoPoint, lo: i64, hi: i64, context: &[u8]) -> [u8; 32] {
+    let mut transcript = Transcript::new(Protocol::Range, context);
+    transcript.append_point(b"x", x_point);
+    transcript.append_message(b"lo", &lo.to_le_bytes());
+    transcript.append_message(b"hi", &hi.to_le_bytes());
+    transcript.sub_context()
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn range_bits_test() {
+        assert_eq!(range_bits(0, 0), 1);
+        assert_eq!(range_bits(0, 1), 1);
+        assert_eq!(range_bits(0, 5), 4);
+        assert_eq!(range_bits(0, 255), 8);
+        assert_eq!(range_bits(0, 256), 16);
+        assert_eq!(range_bits(-100, 100), 8);
+        assert_eq!(range_bits(i64::MIN, i64::MAX), 64);
+    }
+
+    #[test]
+    fn it_works() {
+        let x = Range::commit(5000);
+
+        let proof = Range::prove(&x, 1000, 10000);
+
+        let result = Range::verify(RangeProofWithPublicParams {
+            proof,
+            x: x.comm.point,
+            lo: 1000,
+            hi: 10000,
+        });
+
+        assert!(result);
+    }
+
+    #[test]
+    fn range_protocol_test() {
+        let test_data = vec![
+            (0i64, 0i64, 0i64, true),
+            (10, 10, 20, true),
+            (20, 10, 20, true),
+            (9, 10, 20, false),
+            (21, 10, 20, false),
+            (-50, -100, -10, true),
+            (-5, -100, -10, false),
+            (-101, -100, -10, false),
+            (0, -1, 1, true),
+            (i64::MIN, i64::MIN, i64::MAX, true),
+            (i64::MAX, i64::MIN, i64::MAX, true),
+            (i64::MAX, i64::MIN, i64::MAX - 1, false),
+            (100, 200, 100, false),
+        ];
+        for (value, lo, hi, expect) in test_data {
+            let x = Range::commit(value);
+
+            let proof = Range::prove(&x, lo, hi);
+
+            let result = Range::verify(RangeProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+                lo,
+                hi,
+            });
+
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn random_range_protocol_test() {
+        for _ in 0..20 {
+            let lo = rand::thread_rng().gen_range(-1000000i64..=1000000);
+            let hi = rand::thread_rng().gen_range(lo..=lo + 1000000);
+            let value = rand::thread_rng().gen_range(lo - 1000..=hi + 1000);
+
+            let x = Range::commit(value);
+
+            let proof = Range::prove(&x, lo, hi);
+
+            let result = Range::verify(RangeProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+                lo,
+                hi,
+            });
+
+            assert_eq!(result, lo <= value && value <= hi);
+        }
+    }
+
+    /// a proof for one interval must not verify against another
+    #[test]
+    fn range_wrong_bounds_test() {
+        let x = Range::commit(150);
+
+        let proof = Range::prove(&x, 100, 200);
+
+        assert!(Range::verify(RangeProofWithPublicParams {
+            proof: proof.clone(),
+            x: x.comm.point,
+            lo: 100,
+            hi: 200,
+        }));
+        assert!(!Range::verify(RangeProofWithPublicParams {
+            proof: proof.clone(),
+            x: x.comm.point,
+            lo: 100,
+            hi: 160,
+        }));
+        assert!(!Range::verify(RangeProofWithPublicParams {
+            proof,
+            x: Range::commit(150).comm.point,
+            lo: 100,
+            hi: 200,
+        }));
+    }
+
+    #[test]
+    fn malformed_range_test() {
+        let x = Range::commit(150);
+        let proof = Range::prove(&x, 100, 200);
+
+        let result = Range::try_verify(RangeProofWithPublicParams {
+            proof: proof.clone(),
+            x: x.comm.point,
+            lo: 200,
+            hi: 100,
+        });
+        assert_eq!(result, Err(ProofError::MalformedProof));
+
+        // a proof over 32 bits where [100, 200] takes 8
+        let result = Range::try_verify(RangeProofWithPublicParams {
+            proof: Range::prove(&x, 100, 100000),
+            x: x.comm.point,
+            lo: 100,
+            hi: 200,
+        });
+        assert_eq!(result, Err(ProofError::MalformedProof));
+    }
+
+    /// [x] + G1 in [lo + 1, hi + 1] gives the same [x] - lo·G1 and hi·G1 - [x],
+    /// but a different statement
+    #[test]
+    fn shifted_statement_test() {
+        let x = Range::commit(150);
+        let proof = Range::prove(&x, 100, 200);
+
+        let result = Range::try_verify(RangeProofWithPublicParams {
+            proof,
+            x: x.comm.point + *BASEPOINT_G1,
+            lo: 101,
+            hi: 201,
+        });
+        assert!(result.is_err());
+    }
+
+    /// the aggregated proof inside does not verify on its own
+    #[test]
+    fn lifted_bounds_test() {
+        let x = Range::commit(150);
+        let proof = Range::prove_with_context(&x, 100, 200, b"tx 1");
+
+        let x_vec = vec![
+            x.comm.point - Scalar::from(100u64) * *BASEPOINT_G1,
+            Scalar::from(200u64) * *BASEPOINT_G1 - x.comm.point,
+        ];
+        let params = AggregatedComparisonProofWithPublicParams {
+            proof: proof.bounds,
+            x_vec,
+        };
+        assert!(verify_aggregated_range(params.clone(), 8, b"tx 1").is_err());
+        assert!(verify_aggregated_range(params, 8, &[]).is_err());
+    }
+
+    #[test]
+    fn context_range_protocol_test() {
+        let x = Range::commit(150);
+        let params = RangeProofWithPublicParams {
+            proof: Range::prove_with_context(&x, 100, 200, b"tx 1"),
+            x: x.comm.point,
+            lo: 100,
+            hi: 200,
+        };
+
+        assert!(Range::verify_with_context(params.clone(), b"tx 1"));
+        assert!(!Range::verify_with_context(params.clone(), b"tx 2"));
+        assert_eq!(
+            Range::try_verify(params),
+            Err(ProofError::FirstEquationFailed)
+        );
+    }
+
+    #[test]
+    fn from_opening_range_protocol_test() {
+        let test_data = vec![
+            (150u64, 100i64, 200i64, true),
+            (u64::MAX, 0, i64::MAX, false),
+            (99, 100, 200, false),
+        ];
+        for (value, lo, hi, expect) in test_data {
+            let x = CommittedValue::commit(value);
+
+            let proof = Range::prove(&Range::from_opening(&x), lo, hi);
+
+            let result = Range::verify(RangeProofWithPublicParams {
+                proof,
+                x: x.comm.point,
+                lo,
+                hi,
+            });
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn seeded_rng_range_protocol_test() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let run = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let x = Range::commit_with_rng(150, &mut rng);
+            let proof = Range::prove_with_rng(&x, 100, 200, &mut rng);
+            (x, proof)
+        };
+
+        let (x, proof) = run(7);
+        assert_eq!(run(7), (x.clone(), proof.clone()));
+
+        assert!(Range::verify(RangeProofWithPublicParams {
+            proof,
+            x: x.comm.point,
+            lo: 100,
+            hi: 200,
+        }));
+    }
+
+    #[test]
+    fn bytes_range_protocol_test() {
+        let x = Range::commit(150);
+        let proof = Range::prove(&x, 100, 200);
+
+        let bytes = proof.to_bytes();
+        let decoded = RangeProof::from_bytes(&bytes).unwrap();
+        assert!(Range::verify(RangeProofWithPublicParams {
+            proof: decoded,
+            x: x.comm.point,
+            lo: 100,
+            hi: 200,
+        }));
+
+        assert_eq!(bytes, proof.bounds.to_bytes());
+        assert_eq!(
+            RangeProof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofError::InvalidEncoding)
+        );
+    }
+}
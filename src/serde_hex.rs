@@ -0,0 +1,218 @@
+use crate::codec::Reader;
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use serde::{
+    de::{value::StringDeserializer, Error},
+    Deserialize, Deserializer, Serializer,
+};
+
+/// `#[serde(with = "...")]` helpers encoding points and scalars as the hex of
+/// their canonical bytes; decoding is as strict as `from_bytes`.
+fn decode<'de, D, T>(
+    deserializer: D,
+    read: impl FnOnce(&mut Reader) -> Result<T, crate::ProofError>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let hex_str = String::deserialize(deserializer)?;
+    let bytes = hex::decode(hex_str).map_err(D::Error::custom)?;
+    let mut reader = Reader::new(&bytes);
+    let value = read(&mut reader).map_err(D::Error::custom)?;
+    reader.finish().map_err(D::Error::custom)?;
+    Ok(value)
+}
+
+pub(crate) mod point {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        point: &RistrettoPoint,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(point.compress().as_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RistrettoPoint, D::Error> {
+        decode(deserializer, |reader| reader.point())
+    }
+}
+
+pub(crate) mod compressed {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        point: &CompressedRistretto,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(point.as_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<CompressedRistretto, D::Error> {
+        decode(deserializer, |reader| reader.compressed())
+    }
+}
+
+pub(crate) mod scalar {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(scalar: &Scalar, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(scalar.as_bytes()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scalar, D::Error> {
+        decode(deserializer, |reader| reader.scalar())
+    }
+}
+
+pub(crate) mod points {
+    use super::*;
+    use serde::Serialize;
+
+    pub fn serialize<S: Serializer>(
+        points: &[RistrettoPoint],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        points
+            .iter()
+            .map(|point| hex::encode(point.compress().as_bytes()))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<RistrettoPoint>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|hex_str| point::deserialize(StringDeserializer::<D::Error>::new(hex_str)))
+            .collect()
+    }
+}
+
+pub(crate) mod compressed_points {
+    use super::*;
+    use serde::Serialize;
+
+    pub fn serialize<S: Serializer>(
+        points: &[CompressedRistretto],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        points
+            .iter()
+            .map(|point| hex::encode(point.as_bytes()))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<CompressedRistretto>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|hex_str| compressed::deserialize(StringDeserializer::<D::Error>::new(hex_str)))
+            .collect()
+    }
+}
+
+pub(crate) mod scalars {
+    use super::*;
+    use serde::Serialize;
+
+    pub fn serialize<S: Serializer>(scalars: &[Scalar], serializer: S) -> Result<S::Ok, S::Error> {
+        scalars
+            .iter()
+            .map(|scalar| hex::encode(scalar.as_bytes()))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Scalar>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|hex_str| scalar::deserialize(StringDeserializer::<D::Error>::new(hex_str)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod serde_hex_tests {
+    use crate::{
+        Addition, AdditionProofWithPublicParams, Comparison, ComparisonProofWithPublicParams,
+    };
+
+    #[test]
+    fn json_round_trip_test() {
+        let a = Addition::commit(15);
+        let b = Addition::commit(18);
+        let c = Addition::commit_c_witness(33, a.secret.secret, b.secret.secret);
+        let params = AdditionProofWithPublicParams {
+            proof: Addition::prove(&a, &b, &c),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        };
+
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            json["a_point"],
+            hex::encode(a.comm.point.compress().as_bytes())
+        );
+        assert_eq!(json["proof"]["u"].as_str().unwrap().len(), 64);
+        let decoded: AdditionProofWithPublicParams = serde_json::from_value(json).unwrap();
+        assert!(Addition::verify(decoded));
+
+        let x = Comparison::commit(209348);
+        let params = ComparisonProofWithPublicParams {
+            proof: Comparison::prove(&x),
+            x: x.comm.point,
+        };
+        let json = serde_json::to_string(&params).unwrap();
+        let decoded: ComparisonProofWithPublicParams = serde_json::from_str(&json).unwrap();
+        assert!(Comparison::verify(decoded));
+    }
+
+    #[test]
+    fn strict_decoding_test() {
+        let a = Addition::commit(15);
+        let b = Addition::commit(18);
+        let c = Addition::commit_c_witness(33, a.secret.secret, b.secret.secret);
+        let json = serde_json::to_value(AdditionProofWithPublicParams {
+            proof: Addition::prove(&a, &b, &c),
+            a_point: a.comm.point,
+            b_point: b.comm.point,
+            c_point: c.comm.point,
+        })
+        .unwrap();
+
+        let tampered = [
+            ("u", "ff".repeat(32)),
+            ("d", "ff".repeat(32)),
+            ("v", "00".repeat(31)),
+            ("v", "zz".repeat(32)),
+        ];
+        for (field, value) in tampered.iter() {
+            let mut json = json.clone();
+            json["proof"][field] = value.as_str().into();
+            assert!(serde_json::from_value::<AdditionProofWithPublicParams>(json).is_err());
+        }
+    }
+
+    #[cfg(feature = "serde-secrets")]
+    #[test]
+    fn secrets_round_trip_test() {
+        use crate::CommittedValue;
+
+        let a = CommittedValue::commit(15);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(serde_json::from_str::<CommittedValue>(&json).unwrap(), a);
+    }
+}
@@ -0,0 +1,327 @@
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+use rand_core::{CryptoRng, RngCore};
+use sha3::{Digest, Sha3_512};
+use wedpr_l_crypto_zkp_utils::{BASEPOINT_G1, BASEPOINT_G2};
+
+/// bumped whenever the transcript layout of any protocol changes, so that
+/// proofs from an older layout are rejected instead of misread
+const TRANSCRIPT_VERSION: &[u8] = b"SVMZK transcript v1";
+
+/// Which relation a transcript belongs to. Every protocol starts its
+/// transcript with its own label, so a challenge derived for one relation
+/// never matches the challenge of another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    Addition,
+    Substraction,
+    Multiplication,
+    Division,
+    Equality,
+    Comparison,
+    CompactComparison,
+    Range,
+    DivisionWithRemainder,
+    CheckedSubtraction,
+    CheckedAddition,
+}
+
+impl Protocol {
+    fn label(self) -> &'static [u8] {
+        match self {
+            Protocol::Addition => b"addition",
+            Protocol::Substraction => b"substraction",
+            Protocol::Multiplication => b"multiplication",
+            Protocol::Division => b"division",
+            Protocol::Equality => b"equality",
+            Protocol::Comparison => b"comparison",
+            Protocol::CompactComparison => b"compact comparison",
+            Protocol::Range => b"range",
+            Protocol::DivisionWithRemainder => b"division with remainder",
+            Protocol::CheckedSubtraction => b"checked subtraction",
+            Protocol::CheckedAddition => b"checked addition",
+        }
+    }
+}
+
+/// Fiat–Shamir transcript with labelled appends.
+///
+/// Every message is absorbed as len(label) || label || len(data) || data, so
+/// two different sequences of appends never hash the same bytes. Challenges
+/// are absorbed back, so later challenges depend on earlier ones.
+#[derive(Clone)]
+pub(crate) struct Transcript {
+    hasher: Sha3_512,
+}
+
+impl Transcript {
+    /// `context` is whatever the caller binds the proof to, empty if nothing
+    pub fn new(protocol: Protocol, context: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: Sha3_512::default(),
+        };
+        transcript.append_message(b"version", TRANSCRIPT_VERSION);
+        transcript.append_message(b"protocol", protocol.label());
+        transcript.append_point(b"G1", &BASEPOINT_G1);
+        transcript.append_point(b"G2", &BASEPOINT_G2);
+        transcript.append_message(b"context", context);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.input((label.len() as u64).to_le_bytes());
+        self.hasher.input(label);
+        self.hasher.input((message.len() as u64).to_le_bytes());
+        self.hasher.input(message);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    pub fn append_point(&mut self, label: &[u8], point: &RistrettoPoint) {
+        self.append_message(label, point.compress().as_bytes());
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) {
+        self.append_message(label, scalar.as_bytes());
+    }
+
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Scalar {
+        self.append_message(b"challenge", label);
+        let e = Scalar::from_hash(self.hasher.clone());
+        self.append_scalar(label, &e);
+        e
+    }
+
+    /// The context a composite proof hands its sub-proofs: a challenge over
+    /// the composite's label, the caller's context and the statement appended
+    /// so far, so a sub-proof verifies neither alone nor in another composite.
+    pub fn sub_context(mut self) -> [u8; 32] {
+        self.challenge_scalar(b"sub-proof context").to_bytes()
+    }
+
+    /// Start a nonce RNG bound to everything appended so far, i.e. the
+    /// statement. The transcript itself is left untouched, so the verifier
+    /// never sees anything of it.
+    pub fn build_rng(&self) -> TranscriptRngBuilder {
+        let mut builder = TranscriptRngBuilder {
+            transcript: self.clone(),
+        };
+        builder.transcript.append_message(b"rng", b"nonce");
+        builder
+    }
+}
+
+/// Hedged nonce derivation in the spirit of RFC 6979: nonces are a hash of the
+/// statement, the witness and fresh entropy. A broken RNG then only loses the
+/// randomness, it does not leak the witness through repeated nonces; a good RNG
+/// keeps the nonces unpredictable even when the witness is guessable. Every
+/// prover draws its nonces this way, there is no mode that skips the hash.
+pub(crate) struct TranscriptRngBuilder {
+    transcript: Transcript,
+}
+
+impl TranscriptRngBuilder {
+    pub fn rekey_with_witness_bytes(mut self, label: &[u8], witness: &[u8]) -> Self {
+        self.transcript.append_message(label, witness);
+        self
+    }
+
+    pub fn rekey_with_witness_scalar(self, label: &[u8], witness: &Scalar) -> Self {
+        self.rekey_with_witness_bytes(label, witness.as_bytes())
+    }
+
+    pub fn finalize<R: RngCore + CryptoRng>(mut self, rng: &mut R) -> TranscriptRng {
+        let mut entropy = [0u8; 32];
+        rng.fill_bytes(&mut entropy);
+        self.transcript.append_message(b"entropy", &entropy);
+
+        let mut seed = [0u8; 64];
+        seed.copy_from_slice(&self.transcript.hasher.result());
+        TranscriptRng {
+            seed,
+            counter: 0,
+            block: [0u8; 64],
+            used: 64,
+        }
+    }
+}
+
+/// SHA3-512 in counter mode over the seed the builder derived
+pub(crate) struct TranscriptRng {
+    seed: [u8; 64],
+    counter: u64,
+    block: [u8; 64],
+    used: usize,
+}
+
+impl TranscriptRng {
+    fn refill(&mut self) {
+        let mut hasher = Sha3_512::default();
+        hasher.input(&self.seed[..]);
+        hasher.input(self.counter.to_le_bytes());
+        self.block.copy_from_slice(&hasher.result());
+        self.counter += 1;
+        self.used = 0;
+    }
+}
+
+impl RngCore for TranscriptRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut written = 0;
+        while written < dest.len() {
+            if self.used == self.block.len() {
+                self.refill();
+            }
+            let n = (dest.len() - written).min(self.block.len() - self.used);
+            dest[written..written + n].copy_from_slice(&self.block[self.used..self.used + n]);
+            written += n;
+            self.used += n;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for TranscriptRng {}
+
+#[cfg(test)]
+mod transcript_tests {
+    use super::*;
+
+    #[test]
+    fn protocol_label_test() {
+        let protocols = [
+            Protocol::Addition,
+            Protocol::Substraction,
+            Protocol::Multiplication,
+            Protocol::Division,
+            Protocol::Equality,
+            Protocol::Comparison,
+            Protocol::CompactComparison,
+            Protocol::Range,
+            Protocol::DivisionWithRemainder,
+            Protocol::CheckedSubtraction,
+            Protocol::CheckedAddition,
+        ];
+        for i in 0..protocols.len() {
+            for j in i + 1..protocols.len() {
+                assert_ne!(protocols[i].label(), protocols[j].label());
+            }
+        }
+
+        let challenges: Vec<Scalar> = protocols
+            .iter()
+            .map(|protocol| Transcript::new(*protocol, &[]).challenge_scalar(b"e"))
+            .collect();
+
+        for i in 0..challenges.len() {
+            for j in i + 1..challenges.len() {
+                assert_ne!(challenges[i], challenges[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn labelled_append_test() {
+        let mut t1 = Transcript::new(Protocol::Addition, &[]);
+        t1.append_message(b"a", b"bc");
+        let mut t2 = Transcript::new(Protocol::Addition, &[]);
+        t2.append_message(b"ab", b"c");
+        assert_ne!(t1.challenge_scalar(b"e"), t2.challenge_scalar(b"e"));
+
+        let mut t1 = Transcript::new(Protocol::Addition, &[]);
+        t1.append_message(b"a", b"bc");
+        let mut t2 = Transcript::new(Protocol::Addition, &[]);
+        t2.append_message(b"a", b"bc");
+        assert_eq!(t1.challenge_scalar(b"e"), t2.challenge_scalar(b"e"));
+    }
+
+    #[test]
+    fn chained_challenge_test() {
+        let mut t1 = Transcript::new(Protocol::Comparison, &[]);
+        let mut t2 = Transcript::new(Protocol::Comparison, &[]);
+        let e1 = t1.challenge_scalar(b"y");
+        let e2 = t2.challenge_scalar(b"y");
+        assert_eq!(e1, e2);
+
+        // same label twice still gives a fresh challenge
+        assert_ne!(t1.challenge_scalar(b"y"), e1);
+        assert_ne!(t2.challenge_scalar(b"z"), e2);
+    }
+
+    /// a constant RNG, i.e. one that failed completely
+    struct BrokenRng;
+
+    impl RngCore for BrokenRng {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.iter_mut().for_each(|byte| *byte = 0);
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for BrokenRng {}
+
+    fn nonce<R: RngCore + CryptoRng>(statement: &[u8], witness: &Scalar, rng: &mut R) -> Scalar {
+        let mut transcript = Transcript::new(Protocol::Addition, &[]);
+        transcript.append_message(b"statement", statement);
+        let mut rng = transcript
+            .build_rng()
+            .rekey_with_witness_scalar(b"witness", witness)
+            .finalize(rng);
+        crate::rng::random_scalar(&mut rng)
+    }
+
+    #[test]
+    fn hedged_nonce_test() {
+        let witness = Scalar::from(42u64);
+
+        // with a broken RNG the nonce still differs per statement and witness
+        let n1 = nonce(b"a", &witness, &mut BrokenRng);
+        assert_eq!(n1, nonce(b"a", &witness, &mut BrokenRng));
+        assert_ne!(n1, nonce(b"b", &witness, &mut BrokenRng));
+        assert_ne!(n1, nonce(b"a", &Scalar::from(43u64), &mut BrokenRng));
+
+        // with a working RNG it differs on every call
+        let mut os_rng = rand_core::OsRng;
+        assert_ne!(
+            nonce(b"a", &witness, &mut os_rng),
+            nonce(b"a", &witness, &mut os_rng)
+        );
+    }
+
+    #[test]
+    fn transcript_rng_stream_test() {
+        let transcript = Transcript::new(Protocol::Comparison, &[]);
+        let mut rng1 = transcript.build_rng().finalize(&mut BrokenRng);
+        let mut rng2 = transcript.build_rng().finalize(&mut BrokenRng);
+
+        let mut long = [0u8; 100];
+        rng1.fill_bytes(&mut long);
+        let mut parts = [0u8; 100];
+        rng2.fill_bytes(&mut parts[..30]);
+        rng2.fill_bytes(&mut parts[30..]);
+        assert_eq!(long[..], parts[..]);
+        assert_ne!(long[..64], long[36..]);
+    }
+}